@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use pgarchive::Archive;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::{build_archive, write_archive, Entry};
+
+/// Number of TOC entries in the generated archive.
+const ENTRIES: i64 = 38_000;
+
+fn parse(c: &mut Criterion) {
+    let entries: Vec<Entry> = (1..=ENTRIES)
+        .map(|id| Entry {
+            id,
+            tag: "pizza",
+            desc: "TABLE",
+            section: 2,
+            defn:
+                "CREATE TABLE public.pizza (\n    pizza_id integer NOT NULL,\n    name text\n);\n",
+            copy_stmt: "",
+            data: None,
+        })
+        .collect();
+    let data = build_archive(0, &entries);
+    let file = write_archive(&data);
+
+    let mut group = c.benchmark_group("parse");
+    group.sample_size(20);
+    group.bench_function("memory", |b| {
+        b.iter(|| Archive::parse(&mut &data[..]).unwrap())
+    });
+    group.bench_function("file", |b| b.iter(|| Archive::open(file.path()).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
@@ -0,0 +1,83 @@
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::{build_archive, write_archive, Entry};
+use criterion::{criterion_group, criterion_main, Criterion};
+use pgarchive::{Archive, DataReader, Offset, Section};
+use std::fs::File;
+use std::io::{self, Seek};
+use std::path::Path;
+
+fn read_data(c: &mut Criterion) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, mut f) = Archive::open(path).unwrap();
+    let entry = archive
+        .find_toc_entry(Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+
+    let mut group = c.benchmark_group("read_data");
+    group.bench_function("boxed", |b| {
+        b.iter(|| {
+            let mut data = archive.read_data(&mut f, entry).unwrap();
+            io::copy(&mut data, &mut io::sink()).unwrap()
+        })
+    });
+    group.bench_function("with_data", |b| {
+        b.iter(|| {
+            archive
+                .with_data(&mut f, entry, |data| io::copy(data, &mut io::sink()))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+/// Compare reading data stored in many small chunks with and without the
+/// buffering done by `read_data`.
+fn small_chunks(c: &mut Criterion) {
+    let chunks = vec![b"1\tMargherita\n".to_vec(); 10_000];
+    let file = write_archive(&build_archive(0, &[Entry::table_data(1, "pizza", chunks)]));
+    let mut f = File::open(file.path()).unwrap();
+    let archive = Archive::parse(&mut io::BufReader::new(&mut f)).unwrap();
+    let entry = &archive.toc_entries[0];
+    let Offset::PosSet(offset) = entry.offset else {
+        panic!("entry has no data");
+    };
+
+    let mut group = c.benchmark_group("small_chunks");
+    group.bench_function("read_data", |b| {
+        b.iter(|| {
+            let mut data = archive.read_data(&mut f, entry).unwrap();
+            io::copy(&mut data, &mut io::sink()).unwrap()
+        })
+    });
+    group.bench_function("with_data", |b| {
+        b.iter(|| {
+            archive
+                .with_data(&mut f, entry, |data| io::copy(data, &mut io::sink()))
+                .unwrap()
+        })
+    });
+    let mut buffered = io::BufReader::new(&mut f);
+    group.bench_function("with_data_buffered", |b| {
+        b.iter(|| {
+            archive
+                .with_data(&mut buffered, entry, |data| io::copy(data, &mut io::sink()))
+                .unwrap()
+        })
+    });
+    drop(buffered);
+    group.bench_function("unbuffered", |b| {
+        b.iter(|| {
+            // Skip the block type and dump id preceding the first chunk.
+            let start = offset + 1 + common::INT_SIZE as u64 + 1;
+            f.seek(io::SeekFrom::Start(start)).unwrap();
+            let mut data = DataReader::new(&mut f, common::INT_SIZE);
+            io::copy(&mut data, &mut io::sink()).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, read_data, small_chunks);
+criterion_main!(benches);
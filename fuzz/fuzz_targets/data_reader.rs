@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pgarchive::fuzzing::DataReader;
+use std::io::Read;
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = DataReader::new(data, 4);
+    let mut buffer = [0; 256];
+    while let Ok(n) = reader.read(&mut buffer) {
+        if n == 0 {
+            break;
+        }
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pgarchive::Archive;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Archive::parse(&mut &data[..]);
+});
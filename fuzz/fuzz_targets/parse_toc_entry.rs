@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pgarchive::fuzzing::ReadConfig;
+use pgarchive::TocEntry;
+
+fuzz_target!(|data: &[u8]| {
+    let cfg = ReadConfig {
+        int_size: 4,
+        offset_size: 8,
+        ..ReadConfig::new()
+    };
+    // Version 1.16 reads every field a TOC entry can have.
+    let _ = TocEntry::parse(&mut &data[..], &cfg, (1, 16, 0).into());
+});
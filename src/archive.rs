@@ -1,38 +1,71 @@
-use crate::io::ReadConfig;
-use crate::toc::{read_toc, TocEntry};
-use crate::types::{ArchiveError, CompressionMethod, Section, Version};
+use crate::header::{Header, RawTimestamp};
+use crate::io::{CountingReader, DataReader, ReadConfig};
+use crate::lazy::LazyArchive;
+use crate::options::ParseOptions;
+use crate::schema::{CheckConstraint, Comment, ExtensionInfo, IndexInfo, RoleUsage};
+use crate::settings;
+use crate::sql;
+use crate::toc::{read_toc, DumpId, Toc, TocEntry};
+use crate::types::{
+    ArchiveError, ArchiveFormat, CompressionMethod, DataAvailability, DataFormat, ObjectType,
+    Offset, ParseWarning, Section, Version,
+};
+use crate::versions::FormatFeature;
+#[cfg(feature = "chrono")]
 use chrono::prelude::*;
-use flate2::read::GzDecoder;
-use flate2::read::ZlibDecoder;
+#[cfg(feature = "gzip")]
+use flate2::read::{GzDecoder, ZlibDecoder};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 use std::fs::File;
-use std::io;
+use std::io::{self, BufRead, BufReader, Read, Seek};
+use std::num::NonZeroU64;
+use std::path::Path;
 use std::string::String;
+use std::sync::OnceLock;
+use std::time::Duration;
 
-// Historical version numbers are described in `postgres/src/bin/pg_dump/pg_backup_archiver.h`
+/// Size of the zstd frame header up to and including the dictionary ID.
+#[cfg(feature = "zstd")]
+const ZSTD_MAX_HEADER_SIZE: usize = 10;
 
-/// PostgreSQL 8.0 - add tablespace.
-pub const K_VERS_1_10: Version = (1, 10, 0);
-
-/// PostgreSQL 8.4 - add toc section indicator.
-pub const K_VERS_1_11: Version = (1, 11, 0);
-
-/// PostgreSQL 9.0 - add separate BLOB entries.
-#[allow(dead_code)]
-pub const K_VERS_1_12: Version = (1, 12, 0);
-
-/// PostgreSQL 11 - change search_path behavior.
-#[allow(dead_code)]
-pub const K_VERS_1_13: Version = (1, 13, 0);
-
-/// PostgreSQL 12 - add tableam.
-pub const K_VERS_1_14: Version = (1, 14, 0);
+/// Return the dictionary ID of a zstd frame, if the frame requires a dictionary.
+///
+/// See the frame format in RFC 8878 section 3.1.1.
+#[cfg(feature = "zstd")]
+fn zstd_dictionary_id(header: &[u8]) -> Option<u32> {
+    if header.len() < 5 || header[..4] != [0x28, 0xb5, 0x2f, 0xfd] {
+        return None;
+    }
+    let descriptor = header[4];
+    let single_segment = descriptor & 0x20 != 0;
+    let id_size = [0, 1, 2, 4][(descriptor & 0x03) as usize];
+    let start = if single_segment { 5 } else { 6 };
+    let id = header
+        .get(start..start + id_size)?
+        .iter()
+        .rev()
+        .fold(0u32, |id, &b| (id << 8) | b as u32);
+    (id != 0).then_some(id)
+}
 
-/// PostgreSQL 16 - add compression_algorithm in header.
-pub const K_VERS_1_15: Version = (1, 15, 0);
+/// Types of entries which pg_dump writes for data-only archives.
+const NON_SCHEMA_DESCS: &[&str] = &[
+    "ENCODING",
+    "STDSTRINGS",
+    "SEARCHPATH",
+    "DATABASE",
+    "DATABASE PROPERTIES",
+    "BLOB",
+    "BLOB METADATA",
+];
 
-/// PostgreSQL 17 - BLOB METADATA entries and multiple BLOBS, relkind.
-pub const K_VERS_1_16: Version = (1, 16, 0);
+/// Extract the tablespace name from a `SET default_tablespace` statement.
+fn default_tablespace_setting(stmt: &str) -> Option<String> {
+    let (name, value) = settings::set_statement(stmt)?;
+    name.eq_ignore_ascii_case("default_tablespace")
+        .then_some(value)
+}
 
 /// An object providing access to a PostgreSQL archive
 ///
@@ -42,17 +75,15 @@ pub const K_VERS_1_16: Version = (1, 16, 0);
 /// # Example
 ///
 /// ```rust
-/// use std::fs::File;
 /// use pgarchive::Archive;
 ///
-/// let mut file = File::open("tests/test.pgdump").unwrap();
-/// match Archive::parse(&mut file) {
-///     Ok(archive) => println!("This is a backup of {}", archive.database_name),
+/// match Archive::open("tests/test.pgdump") {
+///     Ok((archive, _file)) => println!("This is a backup of {}", archive.database_name),
 ///     Err(e) => println!("can not read file: {:?}", e),
 /// };
 /// ```
-
-#[derive(Debug, PartialEq)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Archive {
     /// Archive format version.
     ///
@@ -63,8 +94,11 @@ pub struct Archive {
     /// Compression method used for data and blobs
     pub compression_method: CompressionMethod,
 
-    /// Date when the archive was created
-    pub create_date: NaiveDateTime,
+    /// Date when the archive was created, as stored in the archive.
+    ///
+    /// This is the local time of the host running pg_dump. With the `chrono`
+    /// feature [`Archive::create_date`] returns it as a date.
+    pub raw_create_date: RawTimestamp,
 
     /// Name of the database that was dumped
     pub database_name: String,
@@ -75,177 +109,1777 @@ pub struct Archive {
     /// example value is `14.6 (Homebrew)`.
     pub server_version: String,
 
-    /// Version information for pg_dump command that was used to create the archive.
+    /// Version information for pg_dump command that was used to create the archive.
+    ///
+    /// The format of this string differs per PostgreSQL version. An
+    /// example value is `14.6 (Homebrew)`.
+    pub pgdump_version: String,
+
+    /// The table of contents for the archive.
+    ///
+    /// This is a list of all entities in the archive. Call
+    /// [`Archive::reindex`] after modifying it.
+    pub toc_entries: Vec<TocEntry>,
+
+    pub(crate) io_config: ReadConfig,
+
+    /// Whether the archive was read from a source that supports seeking.
+    pub(crate) seekable: bool,
+
+    /// Location of the data for each entry in a tar archive.
+    tar_members: HashMap<DumpId, TarMember>,
+
+    /// The relation kind of entries, which is needed to write the archive.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) relkinds: HashMap<DumpId, i64>,
+
+    /// Problems found while parsing the archive.
+    pub(crate) warnings: Vec<ParseWarning>,
+
+    /// Position in `toc_entries` of the entry with each id.
+    ///
+    /// This is built when it is first needed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ids: OnceLock<HashMap<DumpId, usize>>,
+
+    /// Positions in `toc_entries` of the entries which depend on each entry.
+    ///
+    /// This is built when it is first needed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dependents: OnceLock<HashMap<DumpId, Vec<usize>>>,
+
+    /// Sorted positions of all data blocks in the file.
+    ///
+    /// This is built when it is first needed.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data_offsets: OnceLock<Vec<u64>>,
+}
+
+/// Zstd decoder for a data block, with the already inspected frame header
+/// put back in front.
+#[cfg(feature = "zstd")]
+type ZstdDecoder<R> =
+    zstd::Decoder<'static, BufReader<io::Chain<io::Cursor<Vec<u8>>, DataReader<R>>>>;
+
+/// Reader for the decompressed data of a TOC entry.
+enum DataDecoder<R: Read> {
+    Empty(R),
+    Raw(DataReader<R>),
+    #[cfg(feature = "gzip")]
+    Zlib(ZlibDecoder<DataReader<R>>),
+    // The zstd decoder is much larger than the other readers.
+    #[cfg(feature = "zstd")]
+    Zstd(Box<ZstdDecoder<R>>),
+    Tar(io::Take<R>),
+    #[cfg(feature = "gzip")]
+    TarGzip(GzDecoder<io::Take<R>>),
+}
+
+impl<R: Read> DataDecoder<R> {
+    /// Return the reader for the archive, positioned after the data read so far.
+    fn into_inner(self) -> R {
+        match self {
+            DataDecoder::Empty(r) => r,
+            DataDecoder::Raw(r) => r.into_inner(),
+            #[cfg(feature = "gzip")]
+            DataDecoder::Zlib(r) => r.into_inner().into_inner(),
+            #[cfg(feature = "zstd")]
+            DataDecoder::Zstd(r) => r.finish().into_inner().into_inner().1.into_inner(),
+            DataDecoder::Tar(r) => r.into_inner(),
+            #[cfg(feature = "gzip")]
+            DataDecoder::TarGzip(r) => r.into_inner().into_inner(),
+        }
+    }
+}
+
+impl<R: Read> io::Read for DataDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            DataDecoder::Empty(_) => Ok(0),
+            DataDecoder::Raw(r) => r.read(buf),
+            #[cfg(feature = "gzip")]
+            DataDecoder::Zlib(r) => r.read(buf),
+            #[cfg(feature = "zstd")]
+            DataDecoder::Zstd(r) => r.read(buf),
+            DataDecoder::Tar(r) => r.read(buf),
+            #[cfg(feature = "gzip")]
+            DataDecoder::TarGzip(r) => r.read(buf),
+        }
+    }
+}
+
+/// Reader for the data of a TOC entry, which adds the entry to errors.
+struct EntryData<R: Read> {
+    id: DumpId,
+    desc: String,
+    name: String,
+    decoder: DataDecoder<R>,
+}
+
+impl<R: Read> EntryData<R> {
+    fn new(entry: &TocEntry, decoder: DataDecoder<R>) -> EntryData<R> {
+        EntryData {
+            id: entry.id,
+            desc: entry.desc.clone(),
+            name: entry.qualified_name(),
+            decoder,
+        }
+    }
+}
+
+impl<R: Read> io::Read for EntryData<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.decoder.read(buf).map_err(|source| {
+            io::Error::new(
+                source.kind(),
+                ArchiveError::ReadDataError {
+                    id: self.id,
+                    desc: self.desc.clone(),
+                    name: self.name.clone(),
+                    source,
+                },
+            )
+        })
+    }
+}
+
+/// Reader for the data of a TOC entry which can be rewound, returned by
+/// [`Archive::entry_reader`].
+///
+/// [`reset`](EntryReader::reset) goes back to the start of the data, so it
+/// can be read again without going through the [`Archive`].
+pub struct EntryReader<'a, R: Read> {
+    archive: &'a Archive,
+    entry: &'a TocEntry,
+    /// The reader for the data, or `None` if resetting it failed.
+    data: Option<EntryData<R>>,
+}
+
+impl<R: Read + Seek> EntryReader<'_, R> {
+    /// Go back to the start of the data.
+    ///
+    /// The data is decompressed again from the start. If this fails the
+    /// reader can not be used anymore, and reading returns an error.
+    pub fn reset(&mut self) -> Result<(), ArchiveError> {
+        let data = self.data.take().ok_or_else(reset_failed)?;
+        self.data = Some(
+            self.archive
+                .data_decoder(data.decoder.into_inner(), self.entry)?,
+        );
+        Ok(())
+    }
+}
+
+impl<R: Read> io::Read for EntryReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.data {
+            Some(data) => data.read(buf),
+            None => Err(io::Error::other(reset_failed())),
+        }
+    }
+}
+
+fn reset_failed() -> ArchiveError {
+    ArchiveError::InvalidData("reader can not be used after a failed reset".into())
+}
+
+/// Reader for the data of all tables, returned by [`Archive::read_all_data`].
+struct AllData<'a, R: Read> {
+    archive: &'a Archive,
+    /// The archive reader, when it is not used to read the data of a table.
+    file: Option<R>,
+    entries: std::slice::Iter<'a, TocEntry>,
+    /// Comment line naming the table which is read next.
+    marker: io::Cursor<Vec<u8>>,
+    data: Option<EntryData<R>>,
+}
+
+impl<R: Read + Seek> io::Read for AllData<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let n = self.marker.read(buf)?;
+            if n > 0 {
+                return Ok(n);
+            }
+            if let Some(data) = &mut self.data {
+                let n = data.read(buf)?;
+                if n > 0 {
+                    return Ok(n);
+                }
+                self.file = self.data.take().map(|data| data.decoder.into_inner());
+            }
+            // The reader is lost if opening the data of a table failed.
+            let Some(file) = self.file.take() else {
+                return Ok(0);
+            };
+            let archive = self.archive;
+            let Some(entry) = self
+                .entries
+                .find(|e| e.is(ObjectType::TableData) && archive.data_present(e))
+            else {
+                return Ok(0);
+            };
+            self.data = match archive.data_decoder(file, entry) {
+                Ok(data) => Some(data),
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            };
+            self.marker = io::Cursor::new(
+                format!(
+                    "-- Data for Name: {}; Type: TABLE DATA; Schema: {}\n",
+                    entry.tag, entry.namespace
+                )
+                .into_bytes(),
+            );
+        }
+    }
+}
+
+/// Location of a member in a tar archive.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct TarMember {
+    /// Offset of the member data in the tar file.
+    offset: u64,
+    size: u64,
+    /// Whether the member data is compressed with gzip.
+    gzip: bool,
+}
+
+// The IO configuration is an implementation detail, so it is left out.
+impl fmt::Debug for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Archive")
+            .field("version", &self.version)
+            .field("compression_method", &self.compression_method)
+            .field("raw_create_date", &self.raw_create_date)
+            .field("database_name", &self.database_name)
+            .field("server_version", &self.server_version)
+            .field("pgdump_version", &self.pgdump_version)
+            .field("toc_entries", &self.toc_entries)
+            .finish_non_exhaustive()
+    }
+}
+
+// Archives are compared by content; how integers and offsets were encoded
+// in the file does not matter.
+impl PartialEq for Archive {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.compression_method == other.compression_method
+            && self.raw_create_date == other.raw_create_date
+            && self.database_name == other.database_name
+            && self.server_version == other.server_version
+            && self.pgdump_version == other.pgdump_version
+            && self.toc_entries == other.toc_entries
+    }
+}
+
+/// Show a summary of the archive.
+///
+/// The normal form uses a single line of `key=value` pairs, while the
+/// alternate form (`{:#}`) shows one field per line.
+impl fmt::Display for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let data_entries = self
+            .toc_entries
+            .iter()
+            .filter(|e| e.section == Section::Data)
+            .count();
+        let created = self.raw_create_date;
+        if f.alternate() {
+            writeln!(f, "version: {}", self.version)?;
+            writeln!(f, "compression: {}", self.compression_method)?;
+            writeln!(f, "database: {}", self.database_name)?;
+            writeln!(f, "created: {}", created)?;
+            writeln!(f, "server_version: {}", self.server_version)?;
+            writeln!(f, "toc_entries: {}", self.toc_entries.len())?;
+            write!(f, "data_entries: {}", data_entries)
+        } else {
+            write!(
+                f,
+                "version={} compression={} database={:?} created={} server_version={:?} toc_entries={} data_entries={}",
+                self.version,
+                self.compression_method,
+                self.database_name,
+                created,
+                self.server_version,
+                self.toc_entries.len(),
+                data_entries
+            )
+        }
+    }
+}
+
+impl Archive {
+    /// Read and parse the archive header.
+    ///
+    /// This function reads the archive header from a file-like object, and returns
+    /// a new `Archive` instance.
+    ///
+    /// The header and table of contents are read sequentially, without
+    /// seeking, so this works on pipes and network streams as well. Reading
+    /// stops directly after the table of contents. Since this uses many small
+    /// reads, wrap unbuffered sources such as a [`File`] in a [`BufReader`];
+    /// [`Archive::open`] does this for you.
+    pub fn parse<R: io::Read + ?Sized>(f: &mut R) -> Result<Archive, ArchiveError> {
+        Self::parse_with_options(f, &ParseOptions::new())
+    }
+
+    /// Open an archive file and parse its header and table of contents.
+    ///
+    /// The file is returned together with the archive, so it can be passed to
+    /// [`Archive::read_data`]. An error opening the file is reported as
+    /// [`ArchiveError::OpenError`], which includes the path. See
+    /// [`ArchiveFile`](crate::ArchiveFile) to keep the archive and the file
+    /// together.
+    ///
+    /// ```rust
+    /// use pgarchive::Archive;
+    ///
+    /// let (archive, mut file) = Archive::open("tests/test.pgdump").unwrap();
+    /// println!("This is a backup of {}", archive.database_name);
+    /// ```
+    pub fn open(path: impl AsRef<Path>) -> Result<(Archive, File), ArchiveError> {
+        let path = path.as_ref();
+        let mut file = File::open(path).map_err(|source| ArchiveError::OpenError {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let archive = Self::parse_seekable(&mut file)?;
+        Ok((archive, file))
+    }
+
+    /// Read and parse the archive header and table of contents from a
+    /// seekable source, such as a [`File`].
+    ///
+    /// Reads are buffered, and afterwards `f` is positioned directly after
+    /// the table of contents, as if it had been read without buffering. The
+    /// same `f` can then be passed to [`Archive::read_data`].
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use pgarchive::Archive;
+    ///
+    /// let mut file = File::open("tests/test.pgdump").unwrap();
+    /// let archive = Archive::parse_seekable(&mut file).unwrap();
+    /// println!("This is a backup of {}", archive.database_name);
+    /// ```
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "parse", level = "debug", skip_all, err)
+    )]
+    pub fn parse_seekable<F: Read + Seek>(f: &mut F) -> Result<Archive, ArchiveError> {
+        Self::parse_seekable_with_options(f, &ParseOptions::new())
+    }
+
+    /// Read and parse the archive header and table of contents from a
+    /// seekable source using the given options.
+    ///
+    /// See [`Archive::parse_seekable`].
+    pub fn parse_seekable_with_options<F: Read + Seek>(
+        f: &mut F,
+        options: &ParseOptions,
+    ) -> Result<Archive, ArchiveError> {
+        let start = f.stream_position()?;
+        let mut reader = CountingReader::new(BufReader::new(&mut *f));
+        let archive = Self::read_archive(&mut reader, options).map_err(|e| {
+            let context = reader.field().to_string();
+            reader.wrap_error(e, context)
+        })?;
+        let end = start + reader.position();
+        drop(reader);
+        // The buffer may have read past the end of the table of contents.
+        f.seek(io::SeekFrom::Start(end))?;
+        Ok(archive)
+    }
+
+    /// Read and parse the archive header, and at most `max_entries` TOC entries.
+    ///
+    /// This is useful to diagnose corrupt archives: parsing stops after the
+    /// given number of entries, so you can find the first entry that can not
+    /// be read.
+    pub fn parse_with_limit<R: io::Read + ?Sized>(
+        f: &mut R,
+        max_entries: usize,
+    ) -> Result<Archive, ArchiveError> {
+        Self::parse_with_options(f, &ParseOptions::new().max_entries(max_entries))
+    }
+
+    /// Read and parse the archive header and table of contents from a stream
+    /// which does not support seeking, such as standard input.
+    ///
+    /// Table data can not be read from a stream, so [`Archive::read_data`]
+    /// for the returned archive fails with [`ArchiveError::NotSeekable`].
+    ///
+    /// The stream must be buffered, which makes parsing much faster. The
+    /// reader is left directly after the table of contents, including any
+    /// data already in its buffer, so pass `&mut reader` to continue reading
+    /// the rest of the stream afterwards.
+    ///
+    /// ```rust,no_run
+    /// use pgarchive::Archive;
+    ///
+    /// let archive = Archive::parse_streaming(std::io::stdin().lock()).unwrap();
+    /// println!("This is a backup of {}", archive.database_name);
+    /// ```
+    pub fn parse_streaming<R: io::BufRead>(mut f: R) -> Result<Archive, ArchiveError> {
+        let mut archive = Self::parse(&mut f)?;
+        archive.seekable = false;
+        Ok(archive)
+    }
+
+    /// Read and parse the archive header and table of contents using the
+    /// given options.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(name = "parse", level = "debug", skip_all, err)
+    )]
+    pub fn parse_with_options<R: io::Read + ?Sized>(
+        f: &mut R,
+        options: &ParseOptions,
+    ) -> Result<Archive, ArchiveError> {
+        let mut reader = CountingReader::new(f);
+        Self::read_archive(&mut reader, options).map_err(|e| {
+            let context = reader.field().to_string();
+            reader.wrap_error(e, context)
+        })
+    }
+
+    /// Read and parse an archive in tar format, as made by `pg_dump -Ft`.
+    ///
+    /// The table of contents is read from the `toc.dat` member, and the
+    /// location of the data for each entry is recorded so it can be read
+    /// with [`Archive::read_data`] using the same tar file.
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use pgarchive::Archive;
+    ///
+    /// let mut file = File::open("tests/tar.pgdump").unwrap();
+    /// let archive = Archive::parse_tar(&mut file).unwrap();
+    /// println!("This is a backup of {}", archive.database_name);
+    /// ```
+    pub fn parse_tar<R: io::Read + ?Sized>(f: &mut R) -> Result<Archive, ArchiveError> {
+        let mut archive: Option<Archive> = None;
+        let mut members = HashMap::new();
+        for member in tar::Archive::new(f).entries()? {
+            let mut member = member?;
+            let path = member.path()?;
+            let Some(name) = path.to_str().map(str::to_string) else {
+                continue;
+            };
+            if name == "toc.dat" {
+                archive = Some(Self::parse(&mut member)?);
+                continue;
+            }
+            // Data is stored in members named after the dump id of the entry.
+            let (id, gzip) = match name.strip_suffix(".dat.gz") {
+                Some(id) => (id, true),
+                None => match name.strip_suffix(".dat") {
+                    Some(id) => (id, false),
+                    None => continue,
+                },
+            };
+            if let Ok(id) = id.parse::<i64>().map(DumpId::try_from) {
+                let member = TarMember {
+                    offset: member.raw_file_position(),
+                    size: member.size(),
+                    gzip,
+                };
+                members.insert(id?, member);
+            }
+        }
+        let mut archive = archive.ok_or(ArchiveError::InvalidData(
+            "tar archive does not contain toc.dat".into(),
+        ))?;
+        if archive.io_config.format != ArchiveFormat::Tar {
+            return Err(ArchiveError::InvalidData(
+                "toc.dat is not in tar format".into(),
+            ));
+        }
+        archive.tar_members = members;
+        Ok(archive)
+    }
+
+    /// Read and parse only the archive header.
+    ///
+    /// This stops directly before the table of contents, which makes it much
+    /// faster than [`Archive::parse`] for archives with many TOC entries. The
+    /// table of contents can be read afterwards using [`Archive::parse_toc`].
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use pgarchive::Archive;
+    ///
+    /// let mut file = File::open("tests/test.pgdump").unwrap();
+    /// let header = Archive::parse_header(&mut file).unwrap();
+    /// println!("This is a backup of {}", header.database_name);
+    /// ```
+    pub fn parse_header<R: io::Read + ?Sized>(f: &mut R) -> Result<Header, ArchiveError> {
+        Self::parse_header_with_options(f, &ParseOptions::new())
+    }
+
+    /// Read only the archive header using the given options.
+    ///
+    /// See [`Archive::parse_header`]. The limits in `options` do not carry
+    /// over to the table of contents: pass them to
+    /// [`Archive::parse_toc_with_options`] as well.
+    pub fn parse_header_with_options<R: io::Read + ?Sized>(
+        f: &mut R,
+        options: &ParseOptions,
+    ) -> Result<Header, ArchiveError> {
+        let mut reader = CountingReader::new(f);
+        Header::read(&mut reader, options).map_err(|e| {
+            let context = reader.field().to_string();
+            reader.wrap_error(e, context)
+        })
+    }
+
+    /// Read only the archive header, even if the archive format is newer than
+    /// the supported formats.
+    ///
+    /// The creation date and the database and version names have been stored
+    /// the same way in all formats, so these can usually be read from newer
+    /// archives as well. This is useful to catalog archives made by a newer
+    /// pg_dump. For a newer format a
+    /// [`WarningKind::UnsupportedVersion`](crate::WarningKind::UnsupportedVersion)
+    /// warning is added to the [header warnings](Header::warnings), and
+    /// [`Archive::parse_toc`] fails with the returned header. Use
+    /// [`Archive::parse_toc_with_options`] with
+    /// [lenient](ParseOptions::lenient) options to try anyway.
+    /// Supported formats are read like [`Archive::parse_header`] does.
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use pgarchive::Archive;
+    ///
+    /// let mut file = File::open("tests/test.pgdump").unwrap();
+    /// let header = Archive::parse_header_best_effort(&mut file).unwrap();
+    /// println!("This is a backup of {}", header.database_name);
+    /// ```
+    pub fn parse_header_best_effort<R: io::Read + ?Sized>(
+        f: &mut R,
+    ) -> Result<Header, ArchiveError> {
+        let options = ParseOptions {
+            header_only: true,
+            ..ParseOptions::new()
+        };
+        let mut reader = CountingReader::new(f);
+        Header::read(&mut reader, &options).map_err(|e| {
+            let context = reader.field().to_string();
+            reader.wrap_error(e, context)
+        })
+    }
+
+    /// Read the table of contents following a header read using
+    /// [`Archive::parse_header`].
+    ///
+    /// `f` must be positioned directly after the header.
+    pub fn parse_toc<R: io::Read + ?Sized>(
+        header: Header,
+        f: &mut R,
+    ) -> Result<Archive, ArchiveError> {
+        Self::parse_toc_with_options(header, f, &ParseOptions::new())
+    }
+
+    /// Read the table of contents following a header using the given
+    /// options.
+    ///
+    /// See [`Archive::parse_toc`].
+    pub fn parse_toc_with_options<R: io::Read + ?Sized>(
+        header: Header,
+        f: &mut R,
+        options: &ParseOptions,
+    ) -> Result<Archive, ArchiveError> {
+        let (toc, warnings) = header.read_toc(f, options)?;
+        let mut archive = Archive::from_header(header, toc);
+        archive.warnings.extend(warnings);
+        Ok(archive)
+    }
+
+    /// Read the archive header, and defer reading the table of contents
+    /// until it is needed.
+    ///
+    /// The returned [`LazyArchive`] takes ownership of the reader, which is
+    /// left positioned at the start of the table of contents.
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use pgarchive::Archive;
+    ///
+    /// let mut archive = Archive::parse_lazy(File::open("tests/test.pgdump").unwrap()).unwrap();
+    /// println!("This is a backup of {}", archive.header().database_name);
+    /// // The table of contents is only read here.
+    /// println!("It has {} TOC entries", archive.toc_entries().unwrap().len());
+    /// ```
+    pub fn parse_lazy<R: io::Read>(f: R) -> Result<LazyArchive<R>, ArchiveError> {
+        Self::parse_lazy_with_options(f, &ParseOptions::new())
+    }
+
+    /// Read the archive header using the given options, and defer reading
+    /// the table of contents until it is needed.
+    ///
+    /// See [`Archive::parse_lazy`]. The options are used for both the header
+    /// and the table of contents.
+    pub fn parse_lazy_with_options<R: io::Read>(
+        mut f: R,
+        options: &ParseOptions,
+    ) -> Result<LazyArchive<R>, ArchiveError> {
+        let header = Self::parse_header_with_options(&mut f, options)?;
+        Ok(LazyArchive::new(header, f, options.clone()))
+    }
+
+    fn read_archive<R: io::Read>(
+        f: &mut CountingReader<R>,
+        options: &ParseOptions,
+    ) -> Result<Archive, ArchiveError> {
+        let header = Header::read(f, options)?;
+        let toc = read_toc(f, &header.io_config, header.version, options.max_entries)?;
+        let mut archive = Archive::from_header(header, toc);
+        archive.warnings.extend(f.take_warnings());
+        Ok(archive)
+    }
+
+    pub(crate) fn from_header(header: Header, toc: Toc) -> Archive {
+        Archive {
+            version: header.version,
+            compression_method: header.compression_method,
+            raw_create_date: header.raw_create_date,
+            database_name: header.database_name,
+            server_version: header.server_version,
+            pgdump_version: header.pgdump_version,
+            toc_entries: toc.entries,
+            io_config: header.io_config,
+            seekable: true,
+            tar_members: HashMap::new(),
+            relkinds: toc.relkinds,
+            warnings: header.warnings,
+            ids: OnceLock::new(),
+            dependents: OnceLock::new(),
+            data_offsets: OnceLock::new(),
+        }
+    }
+
+    /// Return the problems found while parsing the archive.
+    ///
+    /// These are problems which do not prevent reading the archive, but may
+    /// indicate that it is damaged or was not made by pg_dump.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Return the date when the archive was created.
+    ///
+    /// This is the local time of the host running pg_dump. See
+    /// [`Archive::created_at_local`] for details. If the archive was parsed
+    /// in [lenient](ParseOptions::lenient) mode and the stored date is not
+    /// valid, the Unix epoch is returned.
+    ///
+    /// This requires the `chrono` feature, which is enabled by default.
+    /// [`raw_create_date`](Archive::raw_create_date) is always available.
+    #[cfg(feature = "chrono")]
+    pub fn create_date(&self) -> NaiveDateTime {
+        self.raw_create_date.to_naive_date_time()
+    }
+
+    /// Return the local time at which the archive was created.
+    ///
+    /// pg_dump stores the creation time as local time on the host running
+    /// pg_dump, together with a flag indicating whether daylight saving time
+    /// was in effect. The timezone itself is not stored, so the absolute
+    /// moment the archive was created can not be determined from the archive
+    /// alone. Use [`Archive::created_at_with_offset`] if you know the UTC
+    /// offset of the host running pg_dump.
+    #[cfg(feature = "chrono")]
+    pub fn created_at_local(&self) -> NaiveDateTime {
+        self.create_date()
+    }
+
+    /// Return the time at which the archive was created, using the given UTC
+    /// offset of the host running pg_dump.
+    ///
+    /// ```rust
+    /// # use std::fs::File;
+    /// # use pgarchive::Archive;
+    /// use chrono::FixedOffset;
+    ///
+    /// # let mut file = File::open("tests/test.pgdump").unwrap();
+    /// # let archive = Archive::parse(&mut file).unwrap();
+    /// let cet = FixedOffset::east_opt(3600).unwrap();
+    /// println!("archive created at {}", archive.created_at_with_offset(cet).to_rfc3339());
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn created_at_with_offset(&self, offset: FixedOffset) -> DateTime<FixedOffset> {
+        DateTime::from_naive_utc_and_offset(self.create_date() - offset, offset)
+    }
+
+    /// Return the size of an archive file in bytes.
+    ///
+    /// Combined with the offsets of TOC entries this can be used to determine
+    /// progress when processing an archive. The position of `f` is not
+    /// changed.
+    pub fn archive_file_size<R: Seek>(mut f: R) -> Result<u64, io::Error> {
+        let position = f.stream_position()?;
+        let size = f.seek(io::SeekFrom::End(0))?;
+        f.seek(io::SeekFrom::Start(position))?;
+        Ok(size)
+    }
+
+    /// Return true if the archive format version supports a feature.
+    ///
+    /// ```rust
+    /// # use std::fs::File;
+    /// # use pgarchive::Archive;
+    /// use pgarchive::versions::FormatFeature;
+    /// # let archive = Archive::parse(&mut File::open("tests/test.pgdump").unwrap()).unwrap();
+    /// if !archive.format_has(FormatFeature::TableAccessMethod) {
+    ///     println!("table access methods are not recorded");
+    /// }
+    /// ```
+    pub fn format_has(&self, feature: FormatFeature) -> bool {
+        self.version >= feature.since()
+    }
+
+    /// Find a TOC entry by name and section.
+    ///
+    /// This function provides a simple method to find a TOC entry, so you
+    /// do not need to iterate over `toc_entries`.
+    ///
+    /// ```rust
+    /// # use std::fs::File;
+    /// # use pgarchive::Archive;
+    /// # let mut file = File::open("tests/test.pgdump").unwrap();
+    /// # let archive = Archive::parse(&mut file).unwrap();
+    /// let employee_toc = archive.find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "employee");
+    /// ```
+    pub fn find_toc_entry(&self, section: Section, desc: &str, tag: &str) -> Option<&TocEntry> {
+        self.toc_entries
+            .iter()
+            .find(|e| e.section == section && e.desc == desc && e.tag == tag)
+    }
+
+    /// Find a TOC entry by its id.
+    ///
+    /// This can be used to look up the [dependencies](TocEntry::dependencies)
+    /// of an entry.
+    ///
+    /// ```rust
+    /// # use std::fs::File;
+    /// # use pgarchive::Archive;
+    /// # let mut file = File::open("tests/test.pgdump").unwrap();
+    /// # let archive = Archive::parse(&mut file).unwrap();
+    /// for entry in &archive.toc_entries {
+    ///     for dep in entry.dependencies.iter().filter_map(|&id| archive.find_toc_entry_by_id(id)) {
+    ///         println!("{} {} depends on {} {}", entry.desc, entry.tag, dep.desc, dep.tag);
+    ///     }
+    /// }
+    /// ```
+    pub fn find_toc_entry_by_id(&self, id: DumpId) -> Option<&TocEntry> {
+        let index = self.ids.get_or_init(|| {
+            let mut index = HashMap::with_capacity(self.toc_entries.len());
+            for (i, entry) in self.toc_entries.iter().enumerate() {
+                // Keep the first entry if an id is used more than once.
+                index.entry(entry.id).or_insert(i);
+            }
+            index
+        });
+        match index.get(&id).and_then(|&i| self.toc_entries.get(i)) {
+            Some(entry) if entry.id == id => Some(entry),
+            // The entries were changed since the index was built.
+            _ => self.toc_entries.iter().find(|e| e.id == id),
+        }
+    }
+
+    /// Find the `TABLE` entry for a `TABLE DATA` entry.
+    ///
+    /// pg_dump records the table as a [dependency](TocEntry::dependencies)
+    /// of its data. `None` is returned for other types of entries.
+    pub fn table_for_data_entry(&self, data_entry: &TocEntry) -> Option<&TocEntry> {
+        if !data_entry.is(ObjectType::TableData) {
+            return None;
+        }
+        data_entry
+            .dependencies
+            .iter()
+            .filter_map(|&id| self.find_toc_entry_by_id(id))
+            .find(|e| e.is(ObjectType::Table))
+    }
+
+    /// Return the `INDEX` entries for a table.
+    ///
+    /// These are the entries which have the `TABLE` entry with id `table_id`
+    /// as a [dependency](TocEntry::dependencies).
+    pub fn index_entries_for_table(&self, table_id: DumpId) -> impl Iterator<Item = &TocEntry> {
+        self.dependents(table_id)
+            .filter(|e| e.is(ObjectType::Index))
+    }
+
+    /// Return the constraint entries for a table.
+    ///
+    /// This includes primary key, unique and exclusion constraints
+    /// (`CONSTRAINT` entries), as well as `FK CONSTRAINT` and
+    /// `CHECK CONSTRAINT` entries, which have the `TABLE` entry with id
+    /// `table_id` as a [dependency](TocEntry::dependencies). pg_dump makes
+    /// foreign keys depend on both the referencing and the referenced table,
+    /// so they are returned for both tables. Check constraints which
+    /// are part of the `CREATE TABLE` statement do not have an entry of their
+    /// own; see [`check_constraints`](Archive::check_constraints) for those.
+    pub fn constraint_entries_for_table(
+        &self,
+        table_id: DumpId,
+    ) -> impl Iterator<Item = &TocEntry> {
+        self.dependents(table_id).filter(|e| {
+            matches!(
+                ObjectType::known(&e.desc),
+                Some(
+                    ObjectType::Constraint | ObjectType::FkConstraint | ObjectType::CheckConstraint
+                )
+            )
+        })
+    }
+
+    /// Return the `TRIGGER` and `RULE` entries for a table.
+    ///
+    /// These are the entries which have the `TABLE` entry with id `table_id`
+    /// as a [dependency](TocEntry::dependencies). This is useful to find the
+    /// triggers to disable before loading data into a table.
+    pub fn trigger_entries_for_table(&self, table_id: DumpId) -> impl Iterator<Item = &TocEntry> {
+        self.dependents(table_id).filter(|e| {
+            matches!(
+                ObjectType::known(&e.desc),
+                Some(ObjectType::Trigger | ObjectType::Rule)
+            )
+        })
+    }
+
+    /// Return the `SEQUENCE` entries owned by a table.
+    ///
+    /// Sequences for identity and serial columns have the `TABLE` entry with
+    /// id `table_id` as a [dependency](TocEntry::dependencies). For identity
+    /// columns the [definition](TocEntry::defn) is an `ALTER TABLE` statement
+    /// adding the `GENERATED ... AS IDENTITY` clause.
+    pub fn sequence_entries_for_table(&self, table_id: DumpId) -> impl Iterator<Item = &TocEntry> {
+        self.dependents(table_id)
+            .filter(|e| e.is(ObjectType::Sequence))
+    }
+
+    /// Return the entries which have the entry with id `id` as a
+    /// [dependency](TocEntry::dependencies), in TOC order.
+    fn dependents(&self, id: DumpId) -> impl Iterator<Item = &TocEntry> {
+        let index = self.dependents.get_or_init(|| {
+            let mut index: HashMap<DumpId, Vec<usize>> = HashMap::new();
+            for (i, entry) in self.toc_entries.iter().enumerate() {
+                for &dep in &entry.dependencies {
+                    let positions = index.entry(dep).or_default();
+                    if positions.last() != Some(&i) {
+                        positions.push(i);
+                    }
+                }
+            }
+            index
+        });
+        index
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|&i| self.toc_entries.get(i))
+            // Skip entries changed since the index was built.
+            .filter(move |e| e.dependencies.contains(&id))
+    }
+
+    /// Rebuild the indexes used to find entries.
+    ///
+    /// Methods such as [`index_entries_for_table`](Archive::index_entries_for_table)
+    /// and [`find_toc_entry_by_id`](Archive::find_toc_entry_by_id) use
+    /// indexes of [`toc_entries`](Archive::toc_entries), which should be
+    /// rebuilt after the entries are changed.
+    pub fn reindex(&mut self) {
+        self.ids = OnceLock::new();
+        self.dependents = OnceLock::new();
+        self.data_offsets = OnceLock::new();
+    }
+
+    /// Find dependencies on entries which are not in the archive.
+    ///
+    /// This happens for partial dumps, for example made with
+    /// `pg_dump --table`. Returns pairs of the id of an entry and the id of
+    /// its missing dependency, in archive order.
+    pub fn validate_dependencies(&self) -> Vec<(DumpId, DumpId)> {
+        let ids: HashSet<DumpId> = self.toc_entries.iter().map(|e| e.id).collect();
+        self.toc_entries
+            .iter()
+            .flat_map(|e| {
+                e.dependencies
+                    .iter()
+                    .filter(|dep| !ids.contains(dep))
+                    .map(move |&dep| (e.id, dep))
+            })
+            .collect()
+    }
+
+    /// Return the TOC entry at a position in the table of contents.
+    ///
+    /// Positions match the order of [`toc_entries`](Archive::toc_entries),
+    /// which is the order used by pg_dump.
+    pub fn entry(&self, index: usize) -> Option<&TocEntry> {
+        self.toc_entries.get(index)
+    }
+
+    /// Return the number of entries in the table of contents.
+    pub fn len(&self) -> usize {
+        self.toc_entries.len()
+    }
+
+    /// Return true if the table of contents has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.toc_entries.is_empty()
+    }
+
+    /// Return all tablespace entries.
+    ///
+    /// This can be used to check if all required tablespaces exist before
+    /// restoring an archive.
+    pub fn tablespace_entries(&self) -> impl Iterator<Item = &TocEntry> {
+        self.toc_entries
+            .iter()
+            .filter(|e| e.is(ObjectType::Tablespace))
+    }
+
+    /// Return the default tablespace set by the archive.
+    ///
+    /// This looks for a `DEFAULT TABLESPACE` entry or an entry containing a
+    /// `SET default_tablespace` statement. Returns `None` if no default
+    /// tablespace is set, which is the common case.
+    pub fn default_tablespace(&self) -> Option<String> {
+        self.toc_entries.iter().find_map(|e| {
+            let name = sql::statements(&e.defn)
+                .into_iter()
+                .find_map(default_tablespace_setting);
+            match name {
+                Some(name) => Some(name),
+                None if e.desc == "DEFAULT TABLESPACE" => Some(e.tag.clone()),
+                None => None,
+            }
+            .filter(|name| !name.is_empty())
+        })
+    }
+
+    /// Return the encoding of the database the archive was made from.
+    ///
+    /// pg_dump writes an `ENCODING` entry which sets `client_encoding` to the
+    /// encoding of the database. All strings in the archive, including table
+    /// data, use this encoding. Returns `None` if the archive has no
+    /// `ENCODING` entry. This is the same as the `client_encoding` of
+    /// [`session_settings`](Archive::session_settings).
+    pub fn database_encoding(&self) -> Option<String> {
+        self.session_settings()
+            .client_encoding
+            .filter(|encoding| !encoding.is_empty())
+    }
+
+    /// Return the extensions used by the archive.
+    ///
+    /// The extension information is extracted from the `CREATE EXTENSION`
+    /// statements of the `EXTENSION` entries. Entries whose statement can
+    /// not be parsed are skipped.
+    pub fn extensions(&self) -> Vec<ExtensionInfo> {
+        self.toc_entries
+            .iter()
+            .filter(|e| e.is(ObjectType::Extension))
+            .filter_map(|e| ExtensionInfo::parse(&e.defn))
+            .collect()
+    }
+
+    /// Return all indexes in the archive.
+    ///
+    /// The index information is extracted from the `CREATE INDEX` statements
+    /// of the `INDEX` entries. Entries whose statement can not be parsed are
+    /// skipped.
+    pub fn indexes(&self) -> Vec<IndexInfo> {
+        self.toc_entries
+            .iter()
+            .filter(|e| e.is(ObjectType::Index))
+            .filter_map(|e| IndexInfo::parse(e.id, &e.defn))
+            .collect()
+    }
+
+    /// Return the comments on database objects.
+    ///
+    /// The comments are extracted from the `COMMENT ON` statements of the
+    /// `COMMENT` entries. Entries whose statement can not be parsed are
+    /// skipped.
+    pub fn comments(&self) -> Vec<Comment> {
+        self.toc_entries
+            .iter()
+            .filter(|e| e.is(ObjectType::Comment))
+            .filter_map(|e| Comment::parse(&e.defn))
+            .collect()
+    }
+
+    /// Return the `CHECK` constraints of a table.
+    ///
+    /// This includes constraints defined in the `CREATE TABLE` statement, and
+    /// constraints which pg_dump adds separately, such as `NOT VALID`
+    /// constraints.
+    pub fn check_constraints(&self, namespace: &str, table: &str) -> Vec<CheckConstraint> {
+        let mut checks = Vec::new();
+        for entry in self.toc_entries.iter().filter(|e| e.namespace == namespace) {
+            match ObjectType::known(&entry.desc) {
+                Some(ObjectType::Table) if entry.tag == table => {
+                    checks.extend(CheckConstraint::parse_create_table(&entry.defn))
+                }
+                Some(ObjectType::CheckConstraint) => {
+                    if let Some((name, check)) = CheckConstraint::parse_alter_table(&entry.defn) {
+                        if name.last().is_some_and(|t| t == table) {
+                            checks.push(check);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        checks
+    }
+
+    /// Return true if the archive looks like it was made with
+    /// `pg_dump --data-only`.
+    ///
+    /// Archives do not record the options used by pg_dump, so this is
+    /// inferred from the TOC entries: a data-only archive has no schema
+    /// objects. Entries which pg_dump always writes, such as `ENCODING` and
+    /// `DATABASE`, and large objects are ignored.
+    pub fn was_data_only(&self) -> bool {
+        self.toc_entries
+            .iter()
+            .all(|e| e.section == Section::Data || NON_SCHEMA_DESCS.contains(&e.desc.as_str()))
+    }
+
+    /// Return true if the archive looks like it was made with
+    /// `pg_dump --schema-only`.
+    ///
+    /// Archives do not record the options used by pg_dump, so this is
+    /// inferred from the TOC entries: a schema-only archive has no entries in
+    /// the data section.
+    ///
+    /// An archive of an empty database is both schema-only and data-only.
+    pub fn was_schema_only(&self) -> bool {
+        !self.toc_entries.iter().any(|e| e.section == Section::Data)
+    }
+
+    /// Return the number of large objects in the archive.
+    ///
+    /// Reading large object data is not supported, so this can be used to
+    /// check if an archive contains data which can not be accessed.
+    ///
+    /// Before format 1.16 pg_dump wrote a `BLOB` entry for every large object.
+    /// Newer versions write `BLOB METADATA` entries, which each create a group
+    /// of large objects.
+    pub fn blob_count(&self) -> usize {
+        self.toc_entries
+            .iter()
+            .map(|e| match ObjectType::known(&e.desc) {
+                Some(ObjectType::Blob) => 1,
+                Some(ObjectType::BlobMetadata) => sql::statements(&e.defn)
+                    .iter()
+                    .filter(|stmt| stmt.contains("lo_create("))
+                    .count(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Return all roles referenced by the archive.
+    ///
+    /// This includes all roles that own objects or that are granted
+    /// privileges, and can be used to determine which roles must exist before
+    /// restoring the archive. Roles are returned in alphabetical order.
+    pub fn roles(&self) -> Vec<RoleUsage> {
+        let mut roles: BTreeMap<String, RoleUsage> = BTreeMap::new();
+        for entry in &self.toc_entries {
+            if !entry.owner.is_empty()
+                && entry.section != Section::Data
+                && !entry.is(ObjectType::Acl)
+                && !entry.is(ObjectType::Comment)
+            {
+                roles.entry(entry.owner.clone()).or_default().owned_objects += 1;
+            }
+            for grant in entry.acl_grants() {
+                if grant.grantee != "PUBLIC" {
+                    roles.entry(grant.grantee).or_default().granted_privileges +=
+                        grant.privileges.len();
+                }
+            }
+        }
+        roles
+            .into_iter()
+            .map(|(name, usage)| RoleUsage { name, ..usage })
+            .collect()
+    }
+
+    /// Write a copy of the archive with the roles which own objects or are
+    /// granted privileges renamed.
+    ///
+    /// `mapping` maps old role names to new role names; other roles are not
+    /// changed. This changes the [owner](TocEntry::owner) of all entries,
+    /// including the `DATABASE` entry, the roles in `ACL` and `DEFAULT ACL`
+    /// entries, and `ALTER ... OWNER TO` statements in definitions. This can
+    /// be used to restore an archive in an environment with different roles.
+    ///
+    /// The archive is written to `out` using [`Archive::write`], copying the
+    /// data from `src`, which must be the file the archive was parsed from.
+    /// Roles named elsewhere in definitions, such as in
+    /// `CREATE POLICY ... TO` or `CREATE USER MAPPING FOR`, are not renamed.
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # use std::collections::HashMap;
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// let (archive, file) = Archive::open("tests/roles.pgdump")?;
+    /// let mapping = HashMap::from([("wichert".to_string(), "postgres".to_string())]);
+    /// let mut out = Vec::new();
+    /// archive.map_owners(file, &mut out, &mapping)?;
+    /// let archive = Archive::parse(&mut &out[..])?;
+    /// assert!(archive.roles().iter().all(|role| role.name != "wichert"));
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn map_owners<R: Read + Seek, W: io::Write>(
+        &self,
+        src: R,
+        out: W,
+        mapping: &HashMap<String, String>,
+    ) -> Result<(), ArchiveError> {
+        let mut archive = self.clone();
+        archive.rename_roles(mapping);
+        archive.write(src, out)
+    }
+
+    /// Rename roles in the table of contents. See [`Archive::map_owners`].
+    fn rename_roles(&mut self, mapping: &HashMap<String, String>) {
+        for entry in &mut self.toc_entries {
+            if let Some(owner) = mapping.get(&entry.owner) {
+                entry.owner = owner.clone();
+            }
+            let acl = matches!(
+                ObjectType::known(&entry.desc),
+                Some(ObjectType::Acl | ObjectType::DefaultAcl)
+            );
+            entry.defn = sql::map_statements(&entry.defn, |stmt| {
+                if acl {
+                    sql::rename_acl_roles(stmt, mapping)
+                } else if sql::strip_keyword(stmt, "ALTER").is_some() {
+                    sql::rename_roles(stmt, &["OWNER TO"], mapping)
+                } else {
+                    stmt.to_string()
+                }
+            });
+        }
+    }
+
+    /// Return whether the data for a TOC entry can be located.
+    ///
+    /// Unlike [`TocEntry::data_availability`] this also handles tar
+    /// archives, which store the data of an entry in a separate member
+    /// instead of recording its position in the table of contents.
+    pub fn data_availability(&self, entry: &TocEntry) -> DataAvailability {
+        if self.data_present(entry) {
+            return DataAvailability::Present;
+        }
+        match self.io_config.format {
+            ArchiveFormat::Tar => DataAvailability::NoData,
+            _ => entry.data_availability(),
+        }
+    }
+
+    /// Access data for a TOC entry.
+    ///
+    /// This function provides access to the data for a TOC entry. This is only
+    /// applicable to `TABLE DATA` and `BLOBS` entries in the `Section::Data`
+    /// section; other entries, such as `TABLE` or `ACL`, return
+    /// [`ArchiveError::NotDataEntry`].
+    ///
+    /// Decompression is automatically handled, so you can read the data directly
+    /// from the returned [`Read`](io::Read) instance. Data compressed with
+    /// Gzip requires the `gzip` feature and Zstandard requires the `zstd`
+    /// feature. Both are enabled by default.
+    /// Long distance matching (`pg_dump --compress=zstd:long`) is supported.
+    ///
+    /// The compression method applies to the whole archive. Data blocks in
+    /// format versions 1.10 to 1.16 only record their block type and dump id,
+    /// and do not have a compression flag of their own.
+    ///
+    /// Archives do not record the size of the uncompressed data, so it can
+    /// not be validated. Data is stored in chunks which are preceded by their
+    /// (compressed) length; reading fails if the archive ends before the end
+    /// of a chunk.
+    ///
+    /// `f` can be any seekable reader, such as a [`File`] or an in-memory
+    /// [`Cursor`](std::io::Cursor). Pass `&mut f` to keep using it after
+    /// reading the data, or pass it by value to get a reader which does not
+    /// borrow it. The returned reader uses the position of `f`, so only one
+    /// reader for a file can be used at a time. See
+    /// [`reader_for`](Archive::reader_for) to read data on several threads.
+    /// Reads from `f` are buffered, so there is no need to wrap it in a
+    /// [`BufReader`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # #[cfg(feature = "gzip")]
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// # let (archive, mut file) = Archive::open("tests/test.pgdump")?;
+    /// let employee_toc = archive
+    ///         .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+    ///         .expect("no data for pizza table present");
+    /// let mut data = archive.read_data(&mut file, &employee_toc)?;
+    /// let mut buffer = Vec::new();
+    /// let size = data.read_to_end(&mut buffer)?;
+    /// println!("the pizza table data has {} bytes of data", size);
+    /// #     Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "gzip"))]
+    /// # fn main() {}
+    /// ```
+    pub fn read_data<'a, R: Read + Seek + Send + 'a>(
+        &self,
+        f: R,
+        entry: &TocEntry,
+    ) -> Result<Box<dyn io::Read + Send + 'a>, ArchiveError> {
+        // Data is stored in chunks preceded by their length, which would
+        // otherwise take two small reads per chunk.
+        Ok(Box::new(self.data_decoder(BufReader::new(f), entry)?))
+    }
+
+    /// Call a function with a reader for the data of a TOC entry.
+    ///
+    /// This works like [`read_data`](Archive::read_data), but the reader is
+    /// not allocated on the heap. This can make a difference when reading
+    /// the data for many small entries.
+    ///
+    /// Unlike `read_data` this does not buffer reads from `f`. Data is stored
+    /// in chunks which are preceded by their length, which takes several
+    /// small reads per chunk. To avoid these, pass a [`BufReader`] for `f`.
+    /// Passing `&mut` a `BufReader` allows using the same buffer for many
+    /// entries.
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # #[cfg(feature = "gzip")]
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// # let (archive, mut file) = Archive::open("tests/test.pgdump")?;
+    /// let entry = archive
+    ///     .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+    ///     .expect("no data for pizza table present");
+    /// let size = archive.with_data(&mut file, entry, |data| {
+    ///     std::io::copy(data, &mut std::io::sink())
+    /// })??;
+    /// println!("the pizza table data has {} bytes of data", size);
+    /// #     Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "gzip"))]
+    /// # fn main() {}
+    /// ```
+    pub fn with_data<T, R: Read + Seek>(
+        &self,
+        f: R,
+        entry: &TocEntry,
+        func: impl FnOnce(&mut dyn io::Read) -> T,
+    ) -> Result<T, ArchiveError> {
+        Ok(func(&mut self.data_decoder(f, entry)?))
+    }
+
+    /// Return the position of the data block following the data of an entry.
+    pub(crate) fn data_end(&self, entry: &TocEntry) -> Option<u64> {
+        let Offset::PosSet(offset) = entry.offset else {
+            return None;
+        };
+        let offsets = self.data_offsets.get_or_init(|| {
+            let mut offsets: Vec<u64> = self
+                .toc_entries
+                .iter()
+                .filter_map(|e| match e.offset {
+                    Offset::PosSet(offset) => Some(offset),
+                    _ => None,
+                })
+                .collect();
+            offsets.sort_unstable();
+            offsets
+        });
+        let next = offsets.partition_point(|&o| o <= offset);
+        offsets.get(next).copied()
+    }
+
+    /// Return true if the archive contains data for an entry.
+    fn data_present(&self, entry: &TocEntry) -> bool {
+        match self.io_config.format {
+            ArchiveFormat::Tar => self.tar_members.contains_key(&entry.id),
+            _ => matches!(entry.offset, Offset::PosSet(_)),
+        }
+    }
+
+    fn data_decoder<R: Read + Seek>(
+        &self,
+        f: R,
+        entry: &TocEntry,
+    ) -> Result<EntryData<R>, ArchiveError> {
+        match self.open_decoder(f, entry) {
+            Ok(decoder) => Ok(EntryData::new(entry, decoder)),
+            Err(ArchiveError::IOError(source)) => Err(ArchiveError::ReadDataError {
+                id: entry.id,
+                desc: entry.desc.clone(),
+                name: entry.qualified_name(),
+                source,
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn open_decoder<R: Read + Seek>(
+        &self,
+        f: R,
+        entry: &TocEntry,
+    ) -> Result<DataDecoder<R>, ArchiveError> {
+        if !self.seekable {
+            return Err(ArchiveError::NotSeekable);
+        }
+        // Archives before format 1.11 do not record the section of entries.
+        let in_data_section =
+            entry.section == Section::Data || !self.format_has(FormatFeature::Section);
+        if !in_data_section
+            || !matches!(
+                ObjectType::known(&entry.desc),
+                Some(ObjectType::TableData | ObjectType::Blobs)
+            )
+        {
+            return Err(ArchiveError::not_data_entry(entry));
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            id = %entry.id,
+            offset = %entry.offset,
+            compression = %self.compression_method,
+            "reading data"
+        );
+        if self.io_config.format == ArchiveFormat::Tar {
+            return self.tar_data_decoder(f, entry);
+        }
+        let reader = self.io_config.data_reader(f, entry, self.data_end(entry))?;
+        // Entries without data have nothing to decompress.
+        if entry.offset == Offset::NoData {
+            return Ok(DataDecoder::Raw(reader));
+        }
+        match self.compression_method {
+            CompressionMethod::None => Ok(DataDecoder::Raw(reader)),
+            #[cfg(feature = "gzip")]
+            CompressionMethod::Gzip(_) => Ok(DataDecoder::Zlib(ZlibDecoder::new(reader))),
+            #[cfg(feature = "zstd")]
+            CompressionMethod::ZSTD => {
+                let mut reader = reader;
+                let mut header = Vec::with_capacity(ZSTD_MAX_HEADER_SIZE);
+                (&mut reader)
+                    .take(ZSTD_MAX_HEADER_SIZE as u64)
+                    .read_to_end(&mut header)?;
+                // An empty block is not a valid zstd frame.
+                if header.is_empty() {
+                    return Ok(DataDecoder::Empty(reader.into_inner()));
+                }
+                if let Some(id) = zstd_dictionary_id(&header) {
+                    return Err(ArchiveError::DictionaryNotSupported(id));
+                }
+                let reader = io::Cursor::new(header).chain(reader);
+                let mut decoder = zstd::Decoder::new(reader)?;
+                // Frames written in long mode may use a window larger than the
+                // decoder accepts by default.
+                decoder.window_log_max(31)?;
+                Ok(DataDecoder::Zstd(Box::new(decoder)))
+            }
+            _ => Err(ArchiveError::CompressionMethodNotSupported(
+                self.compression_method,
+            )),
+        }
+    }
+
+    /// Access data for a TOC entry through a reader which can be rewound.
+    ///
+    /// This works like [`with_data`](Archive::with_data), but returns the
+    /// reader. Use [`EntryReader::reset`] to read the data again, for example
+    /// to make several passes over the data of a table. Like `with_data`
+    /// reads from `f` are not buffered.
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # use std::io::Read;
+    /// # #[cfg(feature = "gzip")]
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// # let (archive, mut file) = Archive::open("tests/test.pgdump")?;
+    /// let entry = archive
+    ///     .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+    ///     .expect("no data for pizza table present");
+    /// let mut data = archive.entry_reader(&mut file, entry)?;
+    /// let size = std::io::copy(&mut data, &mut std::io::sink())?;
+    /// data.reset()?;
+    /// let mut text = String::new();
+    /// data.read_to_string(&mut text)?;
+    /// assert_eq!(text.len() as u64, size);
+    /// #     Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "gzip"))]
+    /// # fn main() {}
+    /// ```
+    pub fn entry_reader<'a, R: Read + Seek>(
+        &'a self,
+        f: R,
+        entry: &'a TocEntry,
+    ) -> Result<EntryReader<'a, R>, ArchiveError> {
+        Ok(EntryReader {
+            archive: self,
+            entry,
+            data: Some(self.data_decoder(f, entry)?),
+        })
+    }
+
+    /// Access data for a TOC entry through a buffered reader.
     ///
-    /// The format of this string differs per PostgreSQL version. An
-    /// example value is `14.6 (Homebrew)`.
-    pub pgdump_version: String,
+    /// This is the same as [`read_data`](Archive::read_data), but the
+    /// returned reader implements [`BufRead`], which is convenient for line
+    /// oriented data such as the output of `COPY`.
+    pub fn read_data_buffered<'a, R: Read + Seek + Send + 'a>(
+        &self,
+        f: R,
+        entry: &TocEntry,
+    ) -> Result<Box<dyn BufRead + Send + 'a>, ArchiveError> {
+        Ok(Box::new(BufReader::new(self.read_data(f, entry)?)))
+    }
 
-    /// The table of contents for the archive.
+    /// Iterate over the lines of `COPY` data for a TOC entry.
     ///
-    /// This is a list of all entities in the archive.
-    pub toc_entries: Vec<TocEntry>,
-
-    io_config: ReadConfig,
-}
-
-impl fmt::Display for Archive {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "version={}.{}.{} compression={}",
-            self.version.0, self.version.1, self.version.2, self.compression_method
-        )
+    /// Lines are returned without the line terminator. Iteration stops at the
+    /// `\.` line marking the end of the data.
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # #[cfg(feature = "gzip")]
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// # let (archive, mut file) = Archive::open("tests/test.pgdump")?;
+    /// let entry = archive
+    ///     .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+    ///     .expect("no data for pizza table present");
+    /// for line in archive.copy_lines(&mut file, entry)? {
+    ///     let row: Vec<String> = line?.split('\t').map(String::from).collect();
+    ///     println!("{:?}", row);
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "gzip"))]
+    /// # fn main() {}
+    /// ```
+    pub fn copy_lines<'a, R: Read + Seek + Send + 'a>(
+        &self,
+        f: R,
+        entry: &TocEntry,
+    ) -> Result<impl Iterator<Item = io::Result<String>> + 'a, ArchiveError> {
+        Ok(self
+            .read_data_buffered(f, entry)?
+            .lines()
+            .take_while(|line| !matches!(line, Ok(l) if l == "\\.")))
     }
-}
 
-impl Archive {
-    /// Read and parse the archive header.
+    /// Read the data of all tables, one after the other.
     ///
-    /// This function reads the archive header from a file-like object, and returns
-    /// a new `Archive` instance.
-    pub fn parse(f: &mut (impl io::Read + ?Sized)) -> Result<Archive, ArchiveError> {
-        let mut buffer = vec![0; 5];
-        f.read_exact(buffer.as_mut_slice())?;
-        if buffer != "PGDMP".as_bytes() {
-            return Err(ArchiveError::InvalidData(
-                "file does not start with PGDMP".into(),
-            ));
+    /// The data of each `TABLE DATA` entry is preceded by a comment line
+    /// naming the table, similar to the comments in the output of pg_restore:
+    ///
+    /// ```text
+    /// -- Data for Name: pizza; Type: TABLE DATA; Schema: public
+    /// ```
+    ///
+    /// Tables are read lazily, in TOC order. Entries without data are skipped.
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # #[cfg(feature = "gzip")]
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// # let (archive, mut file) = Archive::open("tests/test.pgdump")?;
+    /// let mut data = archive.read_all_data(&mut file)?;
+    /// std::io::copy(&mut data, &mut std::io::stdout())?;
+    /// #     Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "gzip"))]
+    /// # fn main() {}
+    /// ```
+    pub fn read_all_data<'a, R: Read + Seek + 'a>(
+        &'a self,
+        f: R,
+    ) -> Result<impl io::Read + 'a, ArchiveError> {
+        if !self.seekable {
+            return Err(ArchiveError::NotSeekable);
         }
+        Ok(AllData {
+            archive: self,
+            file: Some(f),
+            entries: self.toc_entries.iter(),
+            marker: io::Cursor::new(Vec::new()),
+            data: None,
+        })
+    }
 
-        let mut io_config = ReadConfig::new();
-        let version: Version = (
-            io_config.read_byte(f)?,
-            io_config.read_byte(f)?,
-            io_config.read_byte(f)?,
-        );
-
-        if version < K_VERS_1_10 || version > K_VERS_1_16 {
-            return Err(ArchiveError::UnsupportedVersionError(version));
+    /// Return the size of the data for a TOC entry, as stored in the archive.
+    ///
+    /// This is the size of the compressed data, which is found without
+    /// reading or decompressing it. Entries without data have size 0.
+    pub fn table_data_size<R: Read + Seek>(
+        &self,
+        f: R,
+        entry: &TocEntry,
+    ) -> Result<u64, ArchiveError> {
+        if !self.seekable {
+            return Err(ArchiveError::NotSeekable);
         }
+        if self.io_config.format == ArchiveFormat::Tar {
+            return match (self.tar_members.get(&entry.id), entry.offset) {
+                (Some(member), _) => Ok(member.size),
+                (None, Offset::NoData) => Ok(0),
+                (None, _) => Err(ArchiveError::no_data(entry)),
+            };
+        }
+        Ok(self
+            .io_config
+            .data_reader(f, entry, self.data_end(entry))?
+            .skip()?)
+    }
 
-        io_config.int_size = io_config.read_byte(f)? as usize;
-        io_config.offset_size = io_config.read_byte(f)? as usize;
-
-        if io_config.read_byte(f)? != 1 {
-            // 1 = archCustom
+    /// Copy the data for a TOC entry to `dst` without decompressing it.
+    ///
+    /// The data chunks are copied as stored in the archive, including the
+    /// length before each chunk and the zero length which ends the data. The
+    /// block header with the block type and dump id is not copied. This is
+    /// useful to write a new archive with a subset of the tables of an
+    /// existing archive, using the same compression method.
+    ///
+    /// Returns the number of bytes written. Entries without data write
+    /// nothing. Tar archives store data in separate files instead of blocks,
+    /// so their data can not be copied this way.
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// # let (archive, mut file) = Archive::open("tests/test.pgdump")?;
+    /// let entry = archive
+    ///     .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+    ///     .expect("no data for pizza table present");
+    /// let mut blocks = Vec::new();
+    /// let size = archive.copy_table_data_raw(&mut file, entry, &mut blocks)?;
+    /// println!("copied {} bytes of data for the pizza table", size);
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn copy_table_data_raw<R: Read + Seek>(
+        &self,
+        src: R,
+        entry: &TocEntry,
+        dst: &mut impl io::Write,
+    ) -> Result<u64, ArchiveError> {
+        if !self.seekable {
+            return Err(ArchiveError::NotSeekable);
+        }
+        if self.io_config.format == ArchiveFormat::Tar {
             return Err(ArchiveError::InvalidData(
-                "file format must be 1 (custom)".into(),
+                "tar archives do not store data in blocks".into(),
             ));
         }
+        Ok(self
+            .io_config
+            .data_reader(src, entry, self.data_end(entry))?
+            .copy_raw(dst)?)
+    }
 
-        let compression_method = if version >= K_VERS_1_15 {
-            io_config
-                .read_byte(f)?
-                .try_into()
-                .or(Err(ArchiveError::InvalidData(
-                    "invalid compression method".into(),
-                )))?
-        } else {
-            let compression = io_config.read_int(f)?;
-            match compression {
-                -1 => Ok(CompressionMethod::ZSTD),
-                0 => Ok(CompressionMethod::None),
-                1..=9 => Ok(CompressionMethod::Gzip(compression)),
-                _ => Err(ArchiveError::InvalidData(
-                    "invalid compression method".into(),
-                )),
-            }?
-        };
-
-        let created_sec = io_config.read_int(f)?;
-        let created_min = io_config.read_int(f)?;
-        let created_hour = io_config.read_int(f)?;
-        let created_mday = io_config.read_int(f)?;
-        let created_mon = io_config.read_int(f)?;
-        let created_year = io_config.read_int(f)?;
-        let _created_isdst = io_config.read_int(f)?;
-
-        let create_date = NaiveDate::from_ymd_opt(
-            (created_year + 1900) as i32,
-            created_mon as u32,
-            created_mday as u32,
-        )
-        .ok_or(ArchiveError::InvalidData("invalid creation date".into()))?
-        .and_hms_opt(created_hour as u32, created_min as u32, created_sec as u32)
-        .ok_or(ArchiveError::InvalidData(
-            "invalid time in creation date".into(),
-        ))?;
+    /// Estimate how long restoring the table data will take.
+    ///
+    /// This divides the total size of the `TABLE DATA` entries, as returned by
+    /// [`table_data_size`](Archive::table_data_size), by `bytes_per_second`.
+    /// The estimate is very rough: the stored size is the size of the
+    /// compressed data, and the time needed to restore it depends on much more
+    /// than its size, such as indexes, constraints and the database server.
+    /// Only use it to get an order of magnitude.
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # use std::num::NonZeroU64;
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// # let (archive, mut file) = Archive::open("tests/test.pgdump")?;
+    /// let speed = NonZeroU64::new(50 * 1024 * 1024).unwrap();
+    /// let estimate = archive.estimate_restore_time(&mut file, speed)?;
+    /// println!("restoring takes about {} seconds", estimate.as_secs());
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn estimate_restore_time<R: Read + Seek>(
+        &self,
+        mut f: R,
+        bytes_per_second: NonZeroU64,
+    ) -> Result<Duration, ArchiveError> {
+        let mut size = 0;
+        for entry in self
+            .toc_entries
+            .iter()
+            .filter(|e| e.is(ObjectType::TableData))
+        {
+            size += self.table_data_size(&mut f, entry)?;
+        }
+        Ok(Duration::from_secs_f64(
+            size as f64 / bytes_per_second.get() as f64,
+        ))
+    }
 
-        let database_name = io_config.read_string(f)?;
-        let server_version = io_config.read_string(f)?;
-        let pgdump_version = io_config.read_string(f)?;
-        let toc_entries = read_toc(f, &io_config, version)?;
-
-        Ok(Archive {
-            version,
-            compression_method,
-            create_date,
-            database_name,
-            server_version,
-            pgdump_version,
-            toc_entries,
-            io_config,
-        })
+    fn tar_data_decoder<R: Read + Seek>(
+        &self,
+        mut f: R,
+        entry: &TocEntry,
+    ) -> Result<DataDecoder<R>, ArchiveError> {
+        let Some(member) = self.tar_members.get(&entry.id) else {
+            return match entry.offset {
+                Offset::NoData => Ok(DataDecoder::Empty(f)),
+                _ => Err(ArchiveError::no_data(entry)),
+            };
+        };
+        f.seek(io::SeekFrom::Start(member.offset))?;
+        let reader = f.take(member.size);
+        match member.gzip {
+            #[cfg(feature = "gzip")]
+            true => Ok(DataDecoder::TarGzip(GzDecoder::new(reader))),
+            #[cfg(not(feature = "gzip"))]
+            true => Err(ArchiveError::CompressionMethodNotSupported(
+                CompressionMethod::Gzip(-1),
+            )),
+            false => Ok(DataDecoder::Tar(reader)),
+        }
     }
 
-    /// Find a TOC entry by name and section.
+    /// Return the SQL needed to restore a single TOC entry.
     ///
-    /// This function provides a simple method to find a TOC entry, so you
-    /// do not need to iterate over `toc_entries`.
+    /// For entries with table data this is the `COPY` statement followed by
+    /// the data, or the `INSERT` statements for archives made with
+    /// `pg_dump --inserts`. For all other entries, including `SEQUENCE SET`
+    /// entries in the data section, this is the
+    /// [definition](TocEntry::defn) of the entry.
     ///
     /// ```rust
-    /// # use std::fs::File;
     /// # use pgarchive::Archive;
-    /// # let mut file = File::open("tests/test.pgdump").unwrap();
-    /// # let archive = Archive::parse(&mut file).unwrap();
-    /// let employee_toc = archive.find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "employee");
+    /// # #[cfg(feature = "gzip")]
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// # let (archive, mut file) = Archive::open("tests/test.pgdump")?;
+    /// for entry in &archive.toc_entries {
+    ///     print!("{}", archive.restore_sql_for_entry(&mut file, entry)?);
+    /// }
+    /// #     Ok(())
+    /// # }
+    /// # #[cfg(not(feature = "gzip"))]
+    /// # fn main() {}
     /// ```
-    pub fn find_toc_entry(&self, section: Section, desc: &str, tag: &str) -> Option<&TocEntry> {
-        self.toc_entries
-            .iter()
-            .find(|e| e.section == section && e.desc == desc && e.tag == tag)
+    pub fn restore_sql_for_entry<R: Read + Seek>(
+        &self,
+        f: R,
+        entry: &TocEntry,
+    ) -> Result<String, ArchiveError> {
+        if entry.data_format() == DataFormat::None {
+            return Ok(entry.defn.clone());
+        }
+        let mut sql = entry.copy_stmt.clone();
+        self.data_decoder(f, entry)?.read_to_string(&mut sql)?;
+        Ok(sql)
     }
 
-    /// Access data for a TOC entry.
+    /// Open the archive file at `path` to read data.
     ///
-    /// This function provides access to the data for a TOC entry. This is only
-    /// applicable to entries in the `Section::Data` section.
+    /// Readers returned by [`read_data`](Archive::read_data) move the position
+    /// of the file they read from. To read data for several entries at the
+    /// same time, for example on multiple threads, open a file for each
+    /// reader. The archive itself can be shared between threads.
     ///
-    /// Decompression is automatically handled, so you can read the data directly
-    /// from the returned [`Read`](io::Read) instance.
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// let path = "tests/test.pgdump";
+    /// let (archive, _) = Archive::open(path)?;
+    /// std::thread::scope(|s| {
+    ///     for entry in archive.toc_entries.iter().filter(|e| e.desc == "TABLE DATA") {
+    ///         let archive = &archive;
+    ///         s.spawn(move || -> Result<u64, pgarchive::ArchiveError> {
+    ///             let mut file = archive.reader_for(path)?;
+    ///             let mut data = archive.read_data(&mut file, entry)?;
+    ///             Ok(std::io::copy(&mut data, &mut std::io::sink())?)
+    ///         });
+    ///     }
+    /// });
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn reader_for(&self, path: impl AsRef<Path>) -> Result<File, ArchiveError> {
+        let path = path.as_ref();
+        File::open(path).map_err(|source| ArchiveError::OpenError {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// Call a function with the data for every table in the archive.
+    ///
+    /// Unlike [`read_data`](Archive::read_data) this does not need a shared
+    /// [`File`]: the archive at `path` is opened separately for every table, so
+    /// tables can be processed independently of each other. If the `rayon`
+    /// feature is enabled tables are processed in parallel.
     ///
     /// # Example
     ///
     /// ```rust
     /// # use std::fs::File;
+    /// # use std::path::Path;
     /// # use pgarchive::Archive;
+    /// # #[cfg(feature = "gzip")]
     /// # fn main() -> Result<(), pgarchive::ArchiveError> {
-    /// # let mut file = File::open("tests/test.pgdump").unwrap();
-    /// # let archive = Archive::parse(&mut file).unwrap();
-    /// let employee_toc = archive
-    ///         .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
-    ///         .expect("no data for pizza table present");
-    /// let mut data = archive.read_data(&mut file, &employee_toc)?;
-    /// let mut buffer = Vec::new();
-    /// let size = data.read_to_end(&mut buffer)?;
-    /// println!("the pizza table data has {} bytes of data", size);
+    /// let path = Path::new("tests/test.pgdump");
+    /// let archive = Archive::parse(&mut File::open(path)?)?;
+    /// archive.for_each_table(path, |entry, mut data| {
+    ///     let size = std::io::copy(&mut data, &mut std::io::sink()).unwrap();
+    ///     println!("table {} has {} bytes of data", entry.tag, size);
+    /// })?;
     /// #     Ok(())
     /// # }
+    /// # #[cfg(not(feature = "gzip"))]
+    /// # fn main() {}
     /// ```
-    pub fn read_data(
-        &self,
-        f: &mut File,
-        entry: &TocEntry,
-    ) -> Result<Box<dyn io::Read>, ArchiveError> {
-        let reader = self.io_config.read_data(f, entry.offset)?;
-        match self.compression_method {
-            CompressionMethod::None => Ok(reader),
-            CompressionMethod::ZSTD => Ok(Box::new(ZlibDecoder::new(reader))),
-            CompressionMethod::Gzip(_) => Ok(Box::new(GzDecoder::new(reader))),
-            _ => Err(ArchiveError::CompressionMethodNotSupported(
-                self.compression_method,
-            )),
+    pub fn for_each_table<F>(&self, path: &Path, f: F) -> Result<(), ArchiveError>
+    where
+        F: Fn(&TocEntry, Box<dyn io::Read + Send>) + Sync + Send,
+    {
+        let process = |entry: &TocEntry| -> Result<(), ArchiveError> {
+            let file = self.reader_for(path)?;
+            f(entry, self.read_data(file, entry)?);
+            Ok(())
+        };
+        let tables = self
+            .toc_entries
+            .iter()
+            .filter(|e| e.section == Section::Data && e.is(ObjectType::TableData));
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            tables
+                .collect::<Vec<_>>()
+                .into_par_iter()
+                .try_for_each(process)
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            for entry in tables {
+                process(entry)?;
+            }
+            Ok(())
         }
     }
 }
@@ -253,7 +1887,59 @@ impl Archive {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::versions::{K_VERS_1_14, K_VERS_1_16};
     use hex_literal::hex;
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(Archive: Clone, Send, Sync);
+    assert_impl_all!(TocEntry: Clone, Send, Sync);
+    assert_impl_all!(ReadConfig: Clone, Send, Sync);
+    assert_impl_all!(DataDecoder<File>: Send);
+    assert_impl_all!(EntryReader<File>: Send);
+
+    /// Creation date of the test archives, 2022-10-24 07:53:20.
+    fn created() -> RawTimestamp {
+        RawTimestamp {
+            sec: 20,
+            min: 53,
+            hour: 7,
+            mday: 24,
+            mon: 9,
+            year: 122,
+            isdst: 0,
+        }
+    }
+
+    fn archive_with_entries(toc_entries: Vec<TocEntry>) -> Archive {
+        Archive {
+            version: K_VERS_1_14,
+            compression_method: CompressionMethod::None,
+            raw_create_date: created(),
+            database_name: String::from("pizza"),
+            server_version: String::from("14.6"),
+            pgdump_version: String::from("14.6"),
+            toc_entries,
+            io_config: ReadConfig {
+                int_size: 4,
+                offset_size: 8,
+                ..ReadConfig::new()
+            },
+            seekable: true,
+            tar_members: HashMap::new(),
+            relkinds: HashMap::new(),
+            warnings: Vec::new(),
+            ids: OnceLock::new(),
+            dependents: OnceLock::new(),
+            data_offsets: OnceLock::new(),
+        }
+    }
+
+    fn toc_entry(id: u32, section: Section, desc: &str, tag: &str, owner: &str) -> TocEntry {
+        TocEntry {
+            owner: String::from(owner),
+            ..TocEntry::new(DumpId(id), section, desc, tag)
+        }
+    }
 
     #[test]
     fn v14_header() -> Result<(), ArchiveError> {
@@ -268,7 +1954,7 @@ mod tests {
             "00 35 00 00 00" // Minutes
             "00 07 00 00 00" // Hours
             "00 18 00 00 00" // Days
-            "00 0a 00 00 00" // Months
+            "00 09 00 00 00" // Months (since January)
             "00 7a 00 00 00" // Years (since 1900)
             "00 00 00 00 00" // is DST
             "00 07 00 00 00 77 69 63 68 65 72 74" // database name
@@ -278,23 +1964,31 @@ mod tests {
         )[..];
 
         let header = Archive::parse(&mut input)?;
+        assert!(header.format_has(FormatFeature::TableAccessMethod));
+        assert!(!header.format_has(FormatFeature::CompressionMethodByte));
+        assert!(!header.format_has(FormatFeature::RelKind));
         assert_eq!(
             header,
             Archive {
-                version: (1, 14, 0),
-                compression_method: CompressionMethod::ZSTD,
-                create_date: NaiveDate::from_ymd_opt(2022, 10, 24)
-                    .unwrap()
-                    .and_hms_opt(7, 53, 20)
-                    .unwrap(),
+                version: Version::new(1, 14, 0),
+                compression_method: CompressionMethod::Gzip(-1),
+                raw_create_date: created(),
                 database_name: String::from("wichert"),
                 server_version: String::from("14.6 (Homebrew)"),
                 pgdump_version: String::from("14.6 (Homebrew)"),
                 toc_entries: vec![],
                 io_config: ReadConfig {
                     int_size: 4,
-                    offset_size: 8
-                }
+                    offset_size: 8,
+                    ..ReadConfig::new()
+                },
+                seekable: true,
+                tar_members: HashMap::new(),
+                relkinds: HashMap::new(),
+                warnings: Vec::new(),
+                ids: OnceLock::new(),
+                dependents: OnceLock::new(),
+                data_offsets: OnceLock::new(),
             }
         );
         Ok(())
@@ -313,7 +2007,7 @@ mod tests {
             "00 35 00 00 00" // Minutes
             "00 07 00 00 00" // Hours
             "00 18 00 00 00" // Days
-            "00 0a 00 00 00" // Months
+            "00 09 00 00 00" // Months (since January)
             "00 7a 00 00 00" // Years (since 1900)
             "00 00 00 00 00" // is DST
             "00 07 00 00 00 77 69 63 68 65 72 74" // database name
@@ -326,22 +2020,360 @@ mod tests {
         assert_eq!(
             header,
             Archive {
-                version: (1, 15, 0),
+                version: Version::new(1, 15, 0),
                 compression_method: CompressionMethod::LZ4,
-                create_date: NaiveDate::from_ymd_opt(2022, 10, 24)
-                    .unwrap()
-                    .and_hms_opt(7, 53, 20)
-                    .unwrap(),
+                raw_create_date: created(),
                 database_name: String::from("wichert"),
                 server_version: String::from("14.6 (Homebrew)"),
                 pgdump_version: String::from("14.6 (Homebrew)"),
                 toc_entries: vec![],
                 io_config: ReadConfig {
                     int_size: 4,
-                    offset_size: 8
-                }
+                    offset_size: 8,
+                    ..ReadConfig::new()
+                },
+                seekable: true,
+                tar_members: HashMap::new(),
+                relkinds: HashMap::new(),
+                warnings: Vec::new(),
+                ids: OnceLock::new(),
+                dependents: OnceLock::new(),
+                data_offsets: OnceLock::new(),
             }
         );
         Ok(())
     }
+
+    #[test]
+    fn v16_header() -> Result<(), ArchiveError> {
+        let mut input = &hex!(
+            "50 47 44 4d 50" // PGDMP
+            "01 10 00"  // major, minor, patch version
+            "04" // integer size
+            "08" // offset size
+            "01" // header format
+            "03" // Compression method (ZSTD)
+            "00 14 00 00 00" // Seconds
+            "00 35 00 00 00" // Minutes
+            "00 07 00 00 00" // Hours
+            "00 18 00 00 00" // Days
+            "00 09 00 00 00" // Months (since January)
+            "00 7c 00 00 00" // Years (since 1900)
+            "00 00 00 00 00" // is DST
+            "00 07 00 00 00 77 69 63 68 65 72 74" // database name
+            "00 04 00 00 00 31 37 2e 32" // server version
+            "00 04 00 00 00 31 37 2e 32" // pg_dump version
+            "00 00 00 00 00" // toc size
+        )[..];
+
+        let header = Archive::parse(&mut input)?;
+        assert_eq!(header.version, Version::new(1, 16, 0));
+        assert_eq!(header.compression_method, CompressionMethod::ZSTD);
+        assert_eq!(header.server_version, "17.2");
+        for feature in [
+            FormatFeature::TableAccessMethod,
+            FormatFeature::CompressionMethodByte,
+            FormatFeature::RelKind,
+            FormatFeature::BlobMetadata,
+        ] {
+            assert!(header.format_has(feature), "{:?}", feature);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn effective_owner() {
+        let encoding = toc_entry(1, Section::PreData, "ENCODING", "ENCODING", "");
+        let table = toc_entry(2, Section::PreData, "TABLE", "pizza", "wichert");
+
+        let archive = archive_with_entries(vec![encoding.clone(), table.clone()]);
+        assert_eq!(table.effective_owner(&archive), "wichert");
+        assert_eq!(encoding.effective_owner(&archive), "");
+
+        let database = toc_entry(3, Section::PreData, "DATABASE", "pizza", "postgres");
+        let archive = archive_with_entries(vec![encoding.clone(), table.clone(), database]);
+        assert_eq!(table.effective_owner(&archive), "wichert");
+        assert_eq!(encoding.effective_owner(&archive), "postgres");
+    }
+
+    #[test]
+    fn tablespace_entries() {
+        let archive = archive_with_entries(vec![
+            toc_entry(1, Section::PreData, "TABLESPACE", "fast_ssd", "postgres"),
+            toc_entry(2, Section::PreData, "TABLE", "pizza", "wichert"),
+        ]);
+        let tablespaces: Vec<&str> = archive
+            .tablespace_entries()
+            .map(|e| e.tag.as_str())
+            .collect();
+        assert_eq!(tablespaces, vec!["fast_ssd"]);
+    }
+
+    #[test]
+    fn database_encoding() {
+        let mut encoding = toc_entry(1, Section::PreData, "ENCODING", "ENCODING", "");
+        encoding.defn = String::from("SET client_encoding = 'UTF8';\n");
+        let table = toc_entry(2, Section::PreData, "TABLE", "pizza", "wichert");
+        let archive = archive_with_entries(vec![encoding.clone(), table.clone()]);
+        assert_eq!(archive.database_encoding(), Some(String::from("UTF8")));
+
+        encoding.defn = String::from("SET client_encoding TO LATIN1;\n");
+        let archive = archive_with_entries(vec![encoding.clone(), table.clone()]);
+        assert_eq!(archive.database_encoding(), Some(String::from("LATIN1")));
+
+        encoding.defn = String::from("SET client_encoding = 'it''s';\n");
+        let archive = archive_with_entries(vec![encoding, table.clone()]);
+        assert_eq!(archive.database_encoding(), Some(String::from("it's")));
+        assert_eq!(
+            archive.database_encoding(),
+            archive.session_settings().client_encoding
+        );
+
+        let archive = archive_with_entries(vec![table]);
+        assert_eq!(archive.database_encoding(), None);
+    }
+
+    #[test]
+    fn default_tablespace() {
+        let mut setting = toc_entry(1, Section::PreData, "DEFAULT TABLESPACE", "", "postgres");
+        setting.defn = String::from("SET default_tablespace = 'fast_ssd';\n");
+        let table = toc_entry(2, Section::PreData, "TABLE", "pizza", "wichert");
+        let archive = archive_with_entries(vec![table.clone(), setting]);
+        assert_eq!(archive.default_tablespace(), Some(String::from("fast_ssd")));
+
+        let mut setting = toc_entry(1, Section::PreData, "DEFAULT TABLESPACE", "", "postgres");
+        setting.defn = String::from("SET default_tablespace = '';\n");
+        let archive = archive_with_entries(vec![table.clone(), setting]);
+        assert_eq!(archive.default_tablespace(), None);
+
+        let archive = archive_with_entries(vec![table]);
+        assert_eq!(archive.default_tablespace(), None);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn created_at() {
+        let archive = archive_with_entries(vec![]);
+        assert_eq!(archive.created_at_local(), archive.create_date());
+
+        let cet = FixedOffset::east_opt(3600).unwrap();
+        let created = archive.created_at_with_offset(cet);
+        assert_eq!(created.naive_local(), archive.create_date());
+        assert_eq!(created.to_rfc3339(), "2022-10-24T07:53:20+01:00");
+        assert_eq!(
+            created.naive_utc(),
+            NaiveDate::from_ymd_opt(2022, 10, 24)
+                .unwrap()
+                .and_hms_opt(6, 53, 20)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn debug_hides_io_config() {
+        let archive = archive_with_entries(vec![]);
+        let debug = format!("{:?}", archive);
+        assert!(debug.starts_with("Archive { version: Version { major: 1, minor: 14, patch: 0 }, "));
+        assert!(!debug.contains("io_config"));
+        assert!(!debug.contains("int_size"));
+    }
+
+    #[test]
+    fn eq_ignores_io_config() {
+        let archive = archive_with_entries(vec![]);
+        let mut other = archive_with_entries(vec![]);
+        other.io_config.int_size = 8;
+        assert_eq!(archive, other);
+        other.database_name = String::from("other");
+        assert_ne!(archive, other);
+    }
+
+    #[test]
+    fn validate_dependencies() {
+        let table = toc_entry(1, Section::PreData, "TABLE", "pizza", "wichert");
+        let mut data = toc_entry(2, Section::Data, "TABLE DATA", "pizza", "wichert");
+        data.dependencies = vec![DumpId(1), DumpId(42)];
+        let archive = archive_with_entries(vec![table, data]);
+        assert_eq!(
+            archive.validate_dependencies(),
+            vec![(DumpId(2), DumpId(42))]
+        );
+    }
+
+    #[test]
+    fn trigger_entries_for_table() {
+        let pizza = toc_entry(1, Section::PreData, "TABLE", "pizza", "wichert");
+        let topping = toc_entry(2, Section::PreData, "TABLE", "topping", "wichert");
+        let mut trigger = toc_entry(3, Section::PostData, "TRIGGER", "pizza audit", "wichert");
+        trigger.dependencies = vec![DumpId(1)];
+        let mut rule = toc_entry(4, Section::PostData, "RULE", "pizza no_delete", "wichert");
+        rule.dependencies = vec![DumpId(1)];
+        let mut index = toc_entry(5, Section::PostData, "INDEX", "pizza_name", "wichert");
+        index.dependencies = vec![DumpId(1)];
+        let archive = archive_with_entries(vec![pizza, topping, trigger, rule, index]);
+        assert_eq!(
+            archive
+                .trigger_entries_for_table(DumpId(1))
+                .map(|e| e.id)
+                .collect::<Vec<_>>(),
+            [DumpId(3), DumpId(4)]
+        );
+        assert_eq!(archive.trigger_entries_for_table(DumpId(2)).count(), 0);
+    }
+
+    #[test]
+    fn reindex() {
+        let table = toc_entry(1, Section::PreData, "TABLE", "pizza", "wichert");
+        let mut index = toc_entry(2, Section::PostData, "INDEX", "pizza_name", "wichert");
+        index.dependencies = vec![DumpId(1)];
+        let mut archive = archive_with_entries(vec![table, index.clone()]);
+        assert_eq!(archive.index_entries_for_table(DumpId(1)).count(), 1);
+
+        archive.toc_entries.remove(1);
+        assert_eq!(archive.index_entries_for_table(DumpId(1)).count(), 0);
+        index.id = DumpId(3);
+        archive.toc_entries.push(index);
+        archive.reindex();
+        assert_eq!(
+            archive
+                .index_entries_for_table(DumpId(1))
+                .map(|e| e.id)
+                .collect::<Vec<_>>(),
+            [DumpId(3)]
+        );
+    }
+
+    #[test]
+    fn table_for_data_entry() {
+        let sequence = toc_entry(1, Section::PreData, "SEQUENCE", "pizza_id_seq", "wichert");
+        let table = toc_entry(2, Section::PreData, "TABLE", "pizza", "wichert");
+        let mut data = toc_entry(3, Section::Data, "TABLE DATA", "pizza", "wichert");
+        data.dependencies = vec![DumpId(1), DumpId(2)];
+        let mut archive = archive_with_entries(vec![sequence, table, data.clone()]);
+        assert_eq!(
+            archive.find_toc_entry_by_id(DumpId(2)).map(|e| e.id),
+            Some(DumpId(2))
+        );
+        assert_eq!(archive.find_toc_entry_by_id(DumpId(4)), None);
+        assert_eq!(
+            archive.table_for_data_entry(&data).map(|e| e.id),
+            Some(DumpId(2))
+        );
+
+        archive.toc_entries.remove(1);
+        assert_eq!(
+            archive.find_toc_entry_by_id(DumpId(3)).map(|e| e.id),
+            Some(DumpId(3))
+        );
+        assert_eq!(archive.table_for_data_entry(&data), None);
+    }
+
+    #[test]
+    fn entry_by_index() {
+        let archive = archive_with_entries(vec![toc_entry(
+            7,
+            Section::PreData,
+            "TABLE",
+            "pizza",
+            "wichert",
+        )]);
+        assert_eq!(archive.len(), 1);
+        assert!(!archive.is_empty());
+        assert_eq!(archive.entry(0).map(|e| e.id), Some(DumpId(7)));
+        assert_eq!(archive.entry(1), None);
+        assert!(archive_with_entries(vec![]).is_empty());
+    }
+
+    #[test]
+    fn inferred_dump_options() {
+        let settings = [
+            toc_entry(1, Section::PreData, "ENCODING", "ENCODING", ""),
+            toc_entry(2, Section::PreData, "DATABASE", "pizza", "wichert"),
+        ];
+        let table = toc_entry(3, Section::PreData, "TABLE", "pizza", "wichert");
+        let data = toc_entry(4, Section::Data, "TABLE DATA", "pizza", "wichert");
+
+        let full = archive_with_entries([&settings[..], &[table.clone(), data.clone()]].concat());
+        assert!(!full.was_data_only());
+        assert!(!full.was_schema_only());
+
+        let data_only = archive_with_entries([&settings[..], &[data]].concat());
+        assert!(data_only.was_data_only());
+        assert!(!data_only.was_schema_only());
+
+        let schema_only = archive_with_entries([&settings[..], &[table]].concat());
+        assert!(!schema_only.was_data_only());
+        assert!(schema_only.was_schema_only());
+    }
+
+    #[test]
+    fn blob_count() {
+        let mut archive = archive_with_entries(vec![
+            toc_entry(1, Section::PreData, "TABLE", "pizza", "wichert"),
+            toc_entry(2, Section::PreData, "BLOB", "16401", "wichert"),
+            toc_entry(3, Section::PreData, "BLOB", "16402", "wichert"),
+            toc_entry(4, Section::Data, "BLOBS", "BLOBS", ""),
+        ]);
+        assert_eq!(archive.blob_count(), 2);
+
+        let mut metadata = toc_entry(
+            2,
+            Section::PreData,
+            "BLOB METADATA",
+            "16401..16403",
+            "wichert",
+        );
+        metadata.defn = String::from(
+            "SELECT pg_catalog.lo_create('16401');\nSELECT pg_catalog.lo_create('16402');\nSELECT pg_catalog.lo_create('16403');\n",
+        );
+        archive.version = K_VERS_1_16;
+        archive.toc_entries = vec![
+            metadata,
+            toc_entry(3, Section::Data, "BLOBS", "16401..16403", "wichert"),
+        ];
+        assert_eq!(archive.blob_count(), 3);
+
+        archive.toc_entries.clear();
+        assert_eq!(archive.blob_count(), 0);
+    }
+
+    #[test]
+    fn check_constraints() {
+        let mut table = toc_entry(1, Section::PreData, "TABLE", "pizza", "wichert");
+        table.namespace = String::from("public");
+        table.defn = String::from(
+            "CREATE TABLE public.pizza (\n    price numeric,\n    \
+             CONSTRAINT pizza_price_check CHECK ((price > (0)::numeric))\n);\n",
+        );
+        let mut check = toc_entry(
+            2,
+            Section::PostData,
+            "CHECK CONSTRAINT",
+            "pizza name_len",
+            "wichert",
+        );
+        check.namespace = String::from("public");
+        check.defn = String::from(
+            "ALTER TABLE public.pizza\n    ADD CONSTRAINT name_len CHECK ((length(name) < 100)) NOT VALID;\n",
+        );
+        let mut other = toc_entry(3, Section::PreData, "TABLE", "topping", "wichert");
+        other.namespace = String::from("public");
+        other.defn =
+            String::from("CREATE TABLE public.topping (\n    CONSTRAINT c CHECK (true)\n);\n");
+        let archive = archive_with_entries(vec![table, check, other]);
+
+        let names: Vec<_> = archive
+            .check_constraints("public", "pizza")
+            .into_iter()
+            .map(|c| (c.name.unwrap(), c.not_valid))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                (String::from("pizza_price_check"), false),
+                (String::from("name_len"), true)
+            ]
+        );
+        assert!(archive.check_constraints("other", "pizza").is_empty());
+    }
 }
@@ -0,0 +1,229 @@
+use crate::archive::Archive;
+use crate::toc::{DumpId, TocEntry};
+use crate::types::{ArchiveError, Section};
+use std::fs::File;
+use std::io::{self, BufRead, Seek, SeekFrom};
+use std::num::NonZeroU64;
+use std::path::Path;
+use std::time::Duration;
+
+/// An archive together with the file it was read from.
+///
+/// Reading data with [`Archive::read_data`] requires passing the file the
+/// archive was parsed from. `ArchiveFile` keeps the two together, so data is
+/// always read from the right file. The methods to read data only need
+/// `&self`, so entries returned by [`find_toc_entry`](ArchiveFile::find_toc_entry)
+/// can be passed to them directly. Use [`archive`](ArchiveFile::archive) for
+/// everything else.
+///
+/// Readers use positioned reads on the file, so they do not share a file
+/// position. Several readers can be used at the same time, also on different
+/// threads, and all of them read from the file the archive was parsed from.
+///
+/// ```rust
+/// use pgarchive::{ArchiveFile, Section};
+/// use std::io::Read;
+///
+/// # #[cfg(feature = "gzip")]
+/// # fn main() -> Result<(), pgarchive::ArchiveError> {
+/// let archive = ArchiveFile::open("tests/test.pgdump")?;
+/// let entry = archive
+///     .find_toc_entry(Section::Data, "TABLE DATA", "pizza")
+///     .expect("no data for pizza table present");
+/// let mut data = String::new();
+/// archive.read_data(entry)?.read_to_string(&mut data)?;
+/// #     Ok(())
+/// # }
+/// # #[cfg(not(feature = "gzip"))]
+/// # fn main() {}
+/// ```
+#[derive(Debug)]
+pub struct ArchiveFile {
+    archive: Archive,
+    file: File,
+}
+
+impl ArchiveFile {
+    /// Open an archive file and parse its header and table of contents.
+    ///
+    /// See [`Archive::open`].
+    pub fn open(path: impl AsRef<Path>) -> Result<ArchiveFile, ArchiveError> {
+        let (archive, file) = Archive::open(path)?;
+        Ok(ArchiveFile { archive, file })
+    }
+
+    /// Return the parsed archive.
+    pub fn archive(&self) -> &Archive {
+        &self.archive
+    }
+
+    /// Split into the archive and the file it was read from.
+    pub fn into_parts(self) -> (Archive, File) {
+        (self.archive, self.file)
+    }
+
+    /// Find a TOC entry by name and section.
+    ///
+    /// See [`Archive::find_toc_entry`].
+    pub fn find_toc_entry(&self, section: Section, desc: &str, tag: &str) -> Option<&TocEntry> {
+        self.archive.find_toc_entry(section, desc, tag)
+    }
+
+    /// Find a TOC entry by its id.
+    ///
+    /// See [`Archive::find_toc_entry_by_id`].
+    pub fn find_toc_entry_by_id(&self, id: DumpId) -> Option<&TocEntry> {
+        self.archive.find_toc_entry_by_id(id)
+    }
+
+    /// Access data for a TOC entry.
+    ///
+    /// See [`Archive::read_data`].
+    pub fn read_data(
+        &self,
+        entry: &TocEntry,
+    ) -> Result<Box<dyn io::Read + Send + '_>, ArchiveError> {
+        self.archive.read_data(self.reader(), entry)
+    }
+
+    /// Call a function with a reader for the data of a TOC entry.
+    ///
+    /// See [`Archive::with_data`].
+    pub fn with_data<T>(
+        &self,
+        entry: &TocEntry,
+        func: impl FnOnce(&mut dyn io::Read) -> T,
+    ) -> Result<T, ArchiveError> {
+        self.archive.with_data(self.reader(), entry, func)
+    }
+
+    /// Access data for a TOC entry through a buffered reader.
+    ///
+    /// See [`Archive::read_data_buffered`].
+    pub fn read_data_buffered(
+        &self,
+        entry: &TocEntry,
+    ) -> Result<Box<dyn BufRead + Send + '_>, ArchiveError> {
+        self.archive.read_data_buffered(self.reader(), entry)
+    }
+
+    /// Iterate over the lines of `COPY` data for a TOC entry.
+    ///
+    /// See [`Archive::copy_lines`].
+    pub fn copy_lines(
+        &self,
+        entry: &TocEntry,
+    ) -> Result<impl Iterator<Item = io::Result<String>> + '_, ArchiveError> {
+        self.archive.copy_lines(self.reader(), entry)
+    }
+
+    /// Read the data of all tables, one after the other.
+    ///
+    /// See [`Archive::read_all_data`].
+    pub fn read_all_data(&self) -> Result<impl io::Read + '_, ArchiveError> {
+        self.archive.read_all_data(self.reader())
+    }
+
+    /// Return the size of the data for a TOC entry, as stored in the archive.
+    ///
+    /// See [`Archive::table_data_size`].
+    pub fn table_data_size(&self, entry: &TocEntry) -> Result<u64, ArchiveError> {
+        self.archive.table_data_size(self.reader(), entry)
+    }
+
+    /// Estimate how long restoring the table data will take.
+    ///
+    /// See [`Archive::estimate_restore_time`].
+    pub fn estimate_restore_time(
+        &self,
+        bytes_per_second: NonZeroU64,
+    ) -> Result<Duration, ArchiveError> {
+        self.archive
+            .estimate_restore_time(self.reader(), bytes_per_second)
+    }
+
+    /// Copy the data for a TOC entry without decompressing it.
+    ///
+    /// See [`Archive::copy_table_data_raw`].
+    pub fn copy_table_data_raw(
+        &self,
+        entry: &TocEntry,
+        dst: &mut impl io::Write,
+    ) -> Result<u64, ArchiveError> {
+        self.archive.copy_table_data_raw(self.reader(), entry, dst)
+    }
+
+    /// Return the SQL needed to restore a single TOC entry.
+    ///
+    /// See [`Archive::restore_sql_for_entry`].
+    pub fn restore_sql_for_entry(&self, entry: &TocEntry) -> Result<String, ArchiveError> {
+        self.archive.restore_sql_for_entry(self.reader(), entry)
+    }
+
+    /// Return a new reader for the archive file.
+    fn reader(&self) -> FileReader<'_> {
+        FileReader {
+            file: &self.file,
+            position: 0,
+        }
+    }
+}
+
+/// A reader for a file with its own position.
+///
+/// Reads use the position of the reader instead of the position of the file,
+/// so several readers can share the same file.
+struct FileReader<'a> {
+    file: &'a File,
+    position: u64,
+}
+
+impl io::Read for FileReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = read_at(self.file, buf, self.position)?;
+        self.position += size as u64;
+        Ok(size)
+    }
+}
+
+impl Seek for FileReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(position) => {
+                self.position = position;
+                return Ok(position);
+            }
+            SeekFrom::Current(offset) => (self.position, offset),
+            SeekFrom::End(offset) => (self.file.metadata()?.len(), offset),
+        };
+        self.position = base.checked_add_signed(offset).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_at(_file: &File, _buf: &mut [u8], _offset: u64) -> io::Result<usize> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "positioned reads are not supported on this platform",
+    ))
+}
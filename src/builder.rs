@@ -0,0 +1,153 @@
+use crate::archive::Archive;
+use crate::header::{Header, RawTimestamp};
+use crate::io::ReadConfig;
+use crate::toc::{Toc, TocEntry};
+use crate::types::{CompressionMethod, Version};
+use crate::versions::K_VERS_1_16;
+#[cfg(feature = "chrono")]
+use chrono::NaiveDateTime;
+
+/// Builder to construct an [`Archive`] without parsing a file.
+///
+/// This is an escape hatch for tests and tools which need an `Archive`
+/// value. Nothing is validated: the archive is used exactly as described. To
+/// read data with [`Archive::read_data`] the integer and offset sizes and the
+/// offsets of the TOC entries must match the file that is passed in.
+///
+/// ```rust
+/// use pgarchive::{Archive, Section};
+///
+/// let archive = Archive::builder()
+///     .database_name("pizza")
+///     .build();
+/// assert!(archive.find_toc_entry(Section::Data, "TABLE DATA", "pizza").is_none());
+/// ```
+#[derive(Clone, Debug)]
+pub struct ArchiveBuilder {
+    version: Version,
+    compression_method: CompressionMethod,
+    raw_create_date: RawTimestamp,
+    database_name: String,
+    server_version: String,
+    pgdump_version: String,
+    toc_entries: Vec<TocEntry>,
+    int_size: usize,
+    offset_size: usize,
+}
+
+impl Default for ArchiveBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArchiveBuilder {
+    /// Return a builder for an empty archive in the most recent supported
+    /// format, using 4 byte integers and 8 byte offsets.
+    pub fn new() -> ArchiveBuilder {
+        ArchiveBuilder {
+            version: K_VERS_1_16,
+            compression_method: CompressionMethod::None,
+            raw_create_date: RawTimestamp::default(),
+            database_name: String::new(),
+            server_version: String::new(),
+            pgdump_version: String::new(),
+            toc_entries: Vec::new(),
+            int_size: 4,
+            offset_size: 8,
+        }
+    }
+
+    /// Set the archive format version.
+    pub fn version(mut self, version: Version) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Set the compression method used for table data.
+    pub fn compression_method(mut self, method: CompressionMethod) -> Self {
+        self.compression_method = method;
+        self
+    }
+
+    /// Set the date when the archive was created.
+    #[cfg(feature = "chrono")]
+    pub fn create_date(self, date: NaiveDateTime) -> Self {
+        self.raw_create_date(date.into())
+    }
+
+    /// Set the date when the archive was created, as stored in the archive.
+    pub fn raw_create_date(mut self, date: RawTimestamp) -> Self {
+        self.raw_create_date = date;
+        self
+    }
+
+    /// Set the name of the dumped database.
+    pub fn database_name(mut self, name: impl Into<String>) -> Self {
+        self.database_name = name.into();
+        self
+    }
+
+    /// Set the version of the PostgreSQL server.
+    pub fn server_version(mut self, version: impl Into<String>) -> Self {
+        self.server_version = version.into();
+        self
+    }
+
+    /// Set the version of pg_dump.
+    pub fn pgdump_version(mut self, version: impl Into<String>) -> Self {
+        self.pgdump_version = version.into();
+        self
+    }
+
+    /// Add an entry to the table of contents.
+    pub fn toc_entry(mut self, entry: TocEntry) -> Self {
+        self.toc_entries.push(entry);
+        self
+    }
+
+    /// Set the size of integers in the archive, in bytes.
+    pub fn int_size(mut self, size: usize) -> Self {
+        self.int_size = size;
+        self
+    }
+
+    /// Set the size of file offsets in the archive, in bytes.
+    pub fn offset_size(mut self, size: usize) -> Self {
+        self.offset_size = size;
+        self
+    }
+
+    /// Create the archive.
+    pub fn build(self) -> Archive {
+        let header = Header {
+            version: self.version,
+            compression_method: self.compression_method,
+            raw_create_date: self.raw_create_date,
+            database_name: self.database_name,
+            server_version: self.server_version,
+            pgdump_version: self.pgdump_version,
+            io_config: ReadConfig {
+                int_size: self.int_size,
+                offset_size: self.offset_size,
+                ..ReadConfig::new()
+            },
+            size: 0,
+            warnings: Vec::new(),
+        };
+        let toc = Toc {
+            entries: self.toc_entries,
+            ..Toc::default()
+        };
+        Archive::from_header(header, toc)
+    }
+}
+
+impl Archive {
+    /// Return a builder to construct an archive by hand.
+    ///
+    /// See [`ArchiveBuilder`] for details.
+    pub fn builder() -> ArchiveBuilder {
+        ArchiveBuilder::new()
+    }
+}
@@ -0,0 +1,120 @@
+use crate::archive::Archive;
+use crate::sql;
+use crate::toc::TocEntry;
+use crate::types::{ArchiveError, ObjectType};
+use std::collections::BTreeMap;
+
+/// Differences between the tables of contents of two archives.
+///
+/// Entries are matched by namespace, type and tag. An entry is changed if its
+/// SQL definition differs. The order of entries is ignored.
+#[derive(Debug, PartialEq, Default)]
+pub struct TocDiff<'a> {
+    /// Entries only present in the other archive.
+    pub added: Vec<&'a TocEntry>,
+    /// Entries only present in this archive.
+    pub removed: Vec<&'a TocEntry>,
+    /// Entries present in both archives with a different definition, as
+    /// pairs of the entry in this archive and the entry in the other archive.
+    pub changed: Vec<(&'a TocEntry, &'a TocEntry)>,
+}
+
+impl TocDiff<'_> {
+    /// Return true if the archives have the same entries.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+type Key<'a> = (&'a str, &'a str, &'a str);
+
+fn entries_by_key(archive: &Archive) -> BTreeMap<Key<'_>, Vec<&TocEntry>> {
+    let mut map: BTreeMap<Key, Vec<&TocEntry>> = BTreeMap::new();
+    for entry in &archive.toc_entries {
+        map.entry((&entry.namespace, &entry.desc, &entry.tag))
+            .or_default()
+            .push(entry);
+    }
+    map
+}
+
+impl Archive {
+    /// Compare the table of contents of this archive with another archive.
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use pgarchive::Archive;
+    ///
+    /// let old = Archive::parse(&mut File::open("tests/test.pgdump").unwrap()).unwrap();
+    /// let new = Archive::parse(&mut File::open("tests/roles.pgdump").unwrap()).unwrap();
+    /// let diff = old.diff(&new);
+    /// for entry in diff.added {
+    ///     println!("new {} {}.{}", entry.desc, entry.namespace, entry.tag);
+    /// }
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Archive) -> TocDiff<'a> {
+        let ours = entries_by_key(self);
+        let mut theirs = entries_by_key(other);
+        let mut diff = TocDiff::default();
+
+        for (key, entries) in ours {
+            let others = theirs.remove(&key).unwrap_or_default();
+            // Entries sharing a key are matched in archive order.
+            let common = entries.len().min(others.len());
+            for (a, b) in entries.iter().zip(&others) {
+                if a.defn != b.defn {
+                    diff.changed.push((a, b));
+                }
+            }
+            diff.removed.extend(&entries[common..]);
+            diff.added.extend(&others[common..]);
+        }
+        diff.added.extend(theirs.into_values().flatten());
+        diff
+    }
+
+    /// Generate SQL to change the tables in the `old` archive into those of
+    /// the `new` archive.
+    ///
+    /// Removed tables are dropped, and added tables are created using their
+    /// definition from the new archive. Changes to existing tables are not
+    /// translated to SQL: a comment is added for each changed table instead,
+    /// and these must be reviewed manually.
+    ///
+    /// Only tables are compared; other objects such as indexes, views or
+    /// functions are ignored.
+    pub fn schema_diff_sql(old: &Archive, new: &Archive) -> Result<String, ArchiveError> {
+        let diff = old.diff(new);
+        let is_table = |e: &TocEntry| e.is(ObjectType::Table);
+        let mut out = String::new();
+
+        for entry in diff.removed.iter().filter(|e| is_table(e)) {
+            out.push_str(&format!("DROP TABLE IF EXISTS {};\n", table_name(entry)));
+        }
+        for entry in diff.added.iter().filter(|e| is_table(e)) {
+            out.push_str(&entry.defn);
+            if !entry.defn.ends_with('\n') {
+                out.push('\n');
+            }
+        }
+        for (entry, _) in diff.changed.iter().filter(|(e, _)| is_table(e)) {
+            out.push_str(&format!(
+                "-- CHANGED: schema diff not supported; manual review required (table {})\n",
+                table_name(entry)
+            ));
+        }
+        Ok(out)
+    }
+}
+
+fn table_name(entry: &TocEntry) -> String {
+    if entry.namespace.is_empty() {
+        sql::quote_identifier(&entry.tag)
+    } else {
+        format!(
+            "{}.{}",
+            sql::quote_identifier(&entry.namespace),
+            sql::quote_identifier(&entry.tag)
+        )
+    }
+}
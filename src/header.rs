@@ -0,0 +1,468 @@
+use crate::io::{CountingReader, ReadConfig, MAX_INT_SIZE};
+use crate::options::ParseOptions;
+use crate::toc::{read_toc, Toc};
+use crate::types::{ArchiveError, CompressionMethod, ParseWarning, Version, WarningKind};
+use crate::versions::{K_VERS_1_10, K_VERS_1_15, K_VERS_1_16};
+#[cfg(feature = "chrono")]
+use chrono::prelude::*;
+use std::fmt;
+use std::io::Read;
+
+/// The archive header, without the table of contents.
+///
+/// This is returned by [`Archive::parse_header`](crate::Archive::parse_header),
+/// which is much faster than parsing a complete archive with a large table of
+/// contents.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Header {
+    /// Archive format version.
+    pub version: Version,
+
+    /// Compression method used for data and blobs
+    pub compression_method: CompressionMethod,
+
+    /// Date when the archive was created, as local time of the host running
+    /// pg_dump.
+    pub raw_create_date: RawTimestamp,
+
+    /// Name of the database that was dumped
+    pub database_name: String,
+
+    /// Version information for PostgreSQL server that pg_dump was accessing.
+    pub server_version: String,
+
+    /// Version information for pg_dump command that was used to create the archive.
+    pub pgdump_version: String,
+
+    pub(crate) io_config: ReadConfig,
+
+    /// Size of the header in bytes.
+    pub(crate) size: u64,
+
+    pub(crate) warnings: Vec<ParseWarning>,
+}
+
+// The IO configuration is an implementation detail, so it is left out.
+impl fmt::Debug for Header {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Header")
+            .field("version", &self.version)
+            .field("compression_method", &self.compression_method)
+            .field("raw_create_date", &self.raw_create_date)
+            .field("database_name", &self.database_name)
+            .field("server_version", &self.server_version)
+            .field("pgdump_version", &self.pgdump_version)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Header {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.compression_method == other.compression_method
+            && self.raw_create_date == other.raw_create_date
+            && self.database_name == other.database_name
+            && self.server_version == other.server_version
+            && self.pgdump_version == other.pgdump_version
+    }
+}
+
+impl Header {
+    /// Return the date when the archive was created.
+    ///
+    /// See [`Archive::create_date`](crate::Archive::create_date) for details.
+    #[cfg(feature = "chrono")]
+    pub fn create_date(&self) -> NaiveDateTime {
+        self.raw_create_date.to_naive_date_time()
+    }
+
+    /// Return the problems found while parsing the header.
+    pub fn warnings(&self) -> &[ParseWarning] {
+        &self.warnings
+    }
+
+    /// Read the archive header, stopping directly before the table of
+    /// contents.
+    pub(crate) fn read<R: Read>(
+        f: &mut CountingReader<R>,
+        options: &ParseOptions,
+    ) -> Result<Header, ArchiveError> {
+        f.start_field("Archive.magic");
+        let mut buffer = vec![0; 5];
+        f.read_exact(buffer.as_mut_slice())?;
+        if buffer != "PGDMP".as_bytes() {
+            return Err(ArchiveError::BadMagic);
+        }
+
+        f.start_field("Archive.version");
+        let mut io_config = ReadConfig {
+            max_string_length: options.max_string_length,
+            ..ReadConfig::new()
+        };
+        let version = Version::new(
+            io_config.read_byte(f)?,
+            io_config.read_byte(f)?,
+            io_config.read_byte(f)?,
+        );
+
+        if version > K_VERS_1_16 && options.header_only {
+            f.warn(
+                WarningKind::UnsupportedVersion,
+                format!("archive format {} is not supported, TOC skipped", version),
+            );
+        } else if version > K_VERS_1_16 && version.major == K_VERS_1_16.major && options.lenient {
+            f.warn(
+                WarningKind::UnsupportedVersion,
+                format!("archive format {} is not supported", version),
+            );
+        } else if version < K_VERS_1_10 || version > K_VERS_1_16 {
+            return Err(ArchiveError::UnsupportedVersionError(version));
+        }
+
+        f.start_field("Archive.int_size");
+        io_config.int_size = io_config.read_byte(f)? as usize;
+        if io_config.int_size == 0 || io_config.int_size > MAX_INT_SIZE {
+            return Err(ArchiveError::InvalidData(format!(
+                "integer size {} is not supported",
+                io_config.int_size
+            )));
+        }
+        f.start_field("Archive.offset_size");
+        io_config.offset_size = io_config.read_byte(f)? as usize;
+        if io_config.offset_size == 0 || io_config.offset_size > MAX_INT_SIZE {
+            return Err(ArchiveError::InvalidData(format!(
+                "offset size {} is not supported",
+                io_config.offset_size
+            )));
+        }
+
+        f.start_field("Archive.format");
+        io_config.format =
+            io_config
+                .read_byte(f)?
+                .try_into()
+                .or(Err(ArchiveError::InvalidData(
+                    "file format must be 1 (custom) or 3 (tar)".into(),
+                )))?;
+
+        f.start_field("Archive.compression_method");
+        let compression_method = if version >= K_VERS_1_15 {
+            // Unknown methods are kept, so the table of contents can still
+            // be read.
+            CompressionMethod::from_id(io_config.read_byte(f)?)
+        } else {
+            let compression = io_config.read_int(f)?;
+            match compression {
+                // Older versions only support zlib, with -1 (Z_DEFAULT_COMPRESSION)
+                // indicating the default compression level.
+                0 => Ok(CompressionMethod::None),
+                -1 | 1..=9 => Ok(CompressionMethod::Gzip(compression)),
+                _ => Err(ArchiveError::InvalidData(
+                    "invalid compression method".into(),
+                )),
+            }?
+        };
+
+        f.start_field("Archive.create_date");
+        let mut raw_create_date = RawTimestamp {
+            sec: io_config.read_int(f)?,
+            min: io_config.read_int(f)?,
+            hour: io_config.read_int(f)?,
+            mday: io_config.read_int(f)?,
+            mon: io_config.read_int(f)?,
+            year: io_config.read_int(f)?,
+            isdst: 0,
+        };
+        match raw_create_date.validate() {
+            Ok(()) => (),
+            Err(e) if options.lenient => f.warn(WarningKind::InvalidCreateDate, e.to_string()),
+            Err(e) => return Err(e),
+        }
+        f.start_field("Archive.create_date.isdst");
+        raw_create_date.isdst = io_config.read_int(f)?;
+        if raw_create_date.isdst != 0 && raw_create_date.isdst != 1 {
+            f.warn(
+                WarningKind::InvalidDst,
+                format!(
+                    "daylight saving time flag {} is not 0 or 1",
+                    raw_create_date.isdst
+                ),
+            );
+        }
+
+        f.start_field("Archive.database_name");
+        let database_name = io_config.read_string(f)?;
+        if database_name.is_empty() {
+            f.warn(
+                WarningKind::EmptyDatabaseName,
+                "database name is empty".into(),
+            );
+        }
+        f.start_field("Archive.server_version");
+        let server_version = io_config.read_string(f)?;
+        f.start_field("Archive.pgdump_version");
+        let pgdump_version = io_config.read_string(f)?;
+
+        Ok(Header {
+            version,
+            compression_method,
+            raw_create_date,
+            database_name,
+            server_version,
+            pgdump_version,
+            io_config,
+            size: f.position(),
+            warnings: f.take_warnings(),
+        })
+    }
+
+    /// Read the table of contents following the header.
+    ///
+    /// Returns the table of contents, and the warnings for it.
+    pub(crate) fn read_toc<R: Read + ?Sized>(
+        &self,
+        f: &mut R,
+        options: &ParseOptions,
+    ) -> Result<(Toc, Vec<ParseWarning>), ArchiveError> {
+        // A header read by `Archive::parse_header_best_effort` can have any
+        // newer format. Only try the same ones `Header::read` accepts.
+        if self.version > K_VERS_1_16
+            && !(options.lenient && self.version.major == K_VERS_1_16.major)
+        {
+            return Err(ArchiveError::UnsupportedVersionError(self.version));
+        }
+        let mut reader = CountingReader::with_position(f, self.size);
+        let io_config = ReadConfig {
+            max_string_length: options.max_string_length,
+            ..self.io_config.clone()
+        };
+        let toc =
+            read_toc(&mut reader, &io_config, self.version, options.max_entries).map_err(|e| {
+                let context = reader.field().to_string();
+                reader.wrap_error(e, context)
+            })?;
+        Ok((toc, reader.take_warnings()))
+    }
+}
+
+/// Creation time of an archive, as stored in the header.
+///
+/// pg_dump stores the fields of a C `struct tm` with the local time of the
+/// host running pg_dump: months count from 0 and years from 1900. The
+/// timezone is not stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RawTimestamp {
+    /// Seconds after the minute, 0 to 59.
+    pub sec: i64,
+    /// Minutes after the hour, 0 to 59.
+    pub min: i64,
+    /// Hours since midnight, 0 to 23.
+    pub hour: i64,
+    /// Day of the month, 1 to 31.
+    pub mday: i64,
+    /// Months since January, 0 to 11.
+    pub mon: i64,
+    /// Years since 1900.
+    pub year: i64,
+    /// 1 if daylight saving time was in effect, 0 if not.
+    pub isdst: i64,
+}
+
+/// The default timestamp is the Unix epoch.
+impl Default for RawTimestamp {
+    fn default() -> Self {
+        RawTimestamp {
+            sec: 0,
+            min: 0,
+            hour: 0,
+            mday: 1,
+            mon: 0,
+            year: 70,
+            isdst: 0,
+        }
+    }
+}
+
+impl RawTimestamp {
+    /// Check that the fields form a valid date and time.
+    pub(crate) fn validate(&self) -> Result<(), ArchiveError> {
+        let invalid = |field| ArchiveError::InvalidCreateDate {
+            field,
+            sec: self.sec,
+            min: self.min,
+            hour: self.hour,
+            mday: self.mday,
+            mon: self.mon,
+            year: self.year,
+        };
+        let ranges = [
+            ("second", self.sec, 0..=59),
+            ("minute", self.min, 0..=59),
+            ("hour", self.hour, 0..=23),
+            ("day", self.mday, 1..=31),
+            ("month", self.mon, 0..=11),
+            ("year", self.year, -1900..=8099),
+        ];
+        if let Some((field, _, _)) = ranges.iter().find(|(_, v, range)| !range.contains(v)) {
+            return Err(invalid(field));
+        }
+        // The day can still be invalid for the month, such as February 30.
+        if self.mday > days_in_month(self.year + 1900, self.mon + 1) {
+            return Err(invalid("day"));
+        }
+        Ok(())
+    }
+
+    /// Convert to a date, using the Unix epoch if the fields are not valid.
+    #[cfg(feature = "chrono")]
+    pub(crate) fn to_naive_date_time(self) -> NaiveDateTime {
+        if self.validate().is_err() {
+            return NaiveDateTime::default();
+        }
+        NaiveDate::from_ymd_opt(
+            (self.year + 1900) as i32,
+            self.mon as u32 + 1,
+            self.mday as u32,
+        )
+        .and_then(|date| date.and_hms_opt(self.hour as u32, self.min as u32, self.sec as u32))
+        .unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<NaiveDateTime> for RawTimestamp {
+    fn from(date: NaiveDateTime) -> RawTimestamp {
+        RawTimestamp {
+            sec: date.second() as i64,
+            min: date.minute() as i64,
+            hour: date.hour() as i64,
+            mday: date.day() as i64,
+            mon: date.month0() as i64,
+            year: date.year() as i64 - 1900,
+            isdst: 0,
+        }
+    }
+}
+
+/// Show the timestamp in ISO 8601 format, such as `2022-10-24T07:53:20`.
+///
+/// If the fields do not form a valid date and time they are shown as they
+/// are stored, such as `year=123 mon=12 mday=1 hour=0 min=0 sec=0`.
+impl fmt::Display for RawTimestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.validate().is_err() {
+            return write!(
+                f,
+                "year={} mon={} mday={} hour={} min={} sec={}",
+                self.year, self.mon, self.mday, self.hour, self.min, self.sec
+            );
+        }
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            self.year + 1900,
+            self.mon + 1,
+            self.mday,
+            self.hour,
+            self.min,
+            self.sec
+        )
+    }
+}
+
+/// Return the number of days in a month, counting months from 1.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_timestamp() {
+        // Months count from 0, so 0 is January.
+        let date = RawTimestamp {
+            sec: 5,
+            min: 4,
+            hour: 3,
+            mday: 31,
+            mon: 0,
+            year: 124,
+            isdst: 0,
+        };
+        assert!(date.validate().is_ok());
+        assert_eq!(date.to_string(), "2024-01-31T03:04:05");
+        assert_eq!(RawTimestamp::default().to_string(), "1970-01-01T00:00:00");
+
+        let date = RawTimestamp { mon: 12, ..date };
+        assert!(matches!(
+            date.validate(),
+            Err(ArchiveError::InvalidCreateDate { field: "month", .. })
+        ));
+        assert_eq!(
+            date.to_string(),
+            "year=124 mon=12 mday=31 hour=3 min=4 sec=5"
+        );
+        let date_max = RawTimestamp {
+            year: i64::MAX,
+            mon: i64::MAX,
+            ..date
+        };
+        assert_eq!(
+            date_max.to_string(),
+            format!(
+                "year={} mon={} mday=31 hour=3 min=4 sec=5",
+                i64::MAX,
+                i64::MAX
+            )
+        );
+        let date = RawTimestamp {
+            mday: 29,
+            mon: 1,
+            year: 123,
+            ..date
+        };
+        assert!(matches!(
+            date.validate(),
+            Err(ArchiveError::InvalidCreateDate { field: "day", .. })
+        ));
+        assert!(RawTimestamp { year: 124, ..date }.validate().is_ok());
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn raw_timestamp_chrono() {
+        let date = RawTimestamp {
+            sec: 5,
+            min: 4,
+            hour: 3,
+            mday: 31,
+            mon: 0,
+            year: 124,
+            isdst: 1,
+        };
+        let expected = NaiveDate::from_ymd_opt(2024, 1, 31)
+            .unwrap()
+            .and_hms_opt(3, 4, 5)
+            .unwrap();
+        assert_eq!(date.to_naive_date_time(), expected);
+        assert_eq!(
+            RawTimestamp::from(expected),
+            RawTimestamp { isdst: 0, ..date }
+        );
+        assert_eq!(
+            RawTimestamp::default().to_naive_date_time(),
+            NaiveDateTime::default()
+        );
+        let invalid = RawTimestamp { mday: 0, ..date };
+        assert_eq!(invalid.to_naive_date_time(), NaiveDateTime::default());
+    }
+}
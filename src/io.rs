@@ -1,15 +1,25 @@
-use crate::types::{ArchiveError, BlockType, Offset, Oid};
-use std::fs::File;
+use crate::toc::TocEntry;
+use crate::types::{
+    ArchiveError, ArchiveFormat, BlockType, Offset, Oid, ParseWarning, WarningKind,
+};
 use std::io;
 use std::io::prelude::*;
 use std::io::Seek;
-use std::num::ParseIntError;
 use std::string::String;
 
-#[derive(Debug, PartialEq)]
+/// Largest supported size of integers and offsets, in bytes.
+pub const MAX_INT_SIZE: usize = 8;
+
+/// Largest string length for which the buffer is allocated up front.
+const MAX_PREALLOCATED_STRING: i64 = 64 * 1024;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReadConfig {
     pub int_size: usize,
     pub offset_size: usize,
+    pub max_string_length: usize,
+    pub format: ArchiveFormat,
 }
 
 impl Default for ReadConfig {
@@ -22,58 +32,87 @@ impl ReadConfig {
         ReadConfig {
             int_size: 0,
             offset_size: 0,
+            max_string_length: usize::MAX,
+            format: ArchiveFormat::Custom,
         }
     }
 
-    pub fn read_byte(&self, f: &mut (impl Read + ?Sized)) -> io::Result<u8> {
+    pub fn read_byte(&self, f: &mut (impl Read + ?Sized)) -> Result<u8, ArchiveError> {
         let mut buffer: [u8; 1] = [0];
         f.read_exact(&mut buffer)?;
         Ok(buffer[0])
     }
 
-    pub fn read_int(&self, f: &mut (impl Read + ?Sized)) -> io::Result<i64> {
-        read_int(f, self.int_size)
+    pub fn read_int(&self, f: &mut (impl Read + ?Sized)) -> Result<i64, ArchiveError> {
+        Ok(read_int(f, self.int_size)?)
     }
 
-    pub fn read_string(&self, f: &mut (impl Read + ?Sized)) -> io::Result<String> {
+    /// Read a string, returning an empty string for NULL.
+    pub fn read_string(&self, f: &mut (impl Read + ?Sized)) -> Result<String, ArchiveError> {
+        self.read_string_opt(f).map(Option::unwrap_or_default)
+    }
+
+    /// Read a string, returning `None` for NULL.
+    ///
+    /// pg_dump writes NULL as a string with length -1, which is different from
+    /// an empty string with length 0.
+    pub fn read_string_opt(
+        &self,
+        f: &mut (impl Read + ?Sized),
+    ) -> Result<Option<String>, ArchiveError> {
         let length = self.read_int(f)?;
         if length == -1 {
-            return Ok(String::new());
+            return Ok(None);
         }
         if length < 0 {
-            return Err(io::Error::new(
-                io::ErrorKind::Other,
-                "invalid string length",
-            ));
+            return Err(ArchiveError::InvalidData("invalid string length".into()));
+        }
+        if length as u64 > self.max_string_length as u64 {
+            return Err(ArchiveError::StringTooLong {
+                length: length as u64,
+                max: self.max_string_length,
+            });
         }
-        let mut buffer = vec![0; length as usize];
-        f.read_exact(buffer.as_mut_slice())?;
-        let s = String::from_utf8(buffer)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
-        Ok(s)
+        // Strings are read with a single allocation. Only the buffer for
+        // very long strings grows while reading, so a corrupt length does
+        // not allocate more memory than the data that is present.
+        let mut buffer = Vec::with_capacity(length.min(MAX_PREALLOCATED_STRING) as usize);
+        f.take(length as u64).read_to_end(&mut buffer)?;
+        if buffer.len() as u64 != length as u64 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        // The field name is filled in by `CountingReader::wrap_error`.
+        String::from_utf8(buffer)
+            .map(Some)
+            .map_err(|source| ArchiveError::StringNotUtf8 {
+                field: None,
+                source,
+            })
     }
 
-    pub fn read_int_bool(&self, f: &mut (impl Read + ?Sized)) -> io::Result<bool> {
+    pub fn read_int_bool(&self, f: &mut (impl Read + ?Sized)) -> Result<bool, ArchiveError> {
         self.read_int(f).map(|v| v != 0)
     }
 
-    pub fn read_string_bool(&self, f: &mut (impl Read + ?Sized)) -> io::Result<bool> {
+    pub fn read_string_bool(&self, f: &mut (impl Read + ?Sized)) -> Result<bool, ArchiveError> {
         self.read_string(f).map(|v| v == "true")
     }
 
-    pub fn read_oid(&self, f: &mut (impl Read + ?Sized)) -> io::Result<Oid> {
-        let v = self.read_string(f)?;
-        Oid::from_str_radix(v.as_str(), 10)
-            .map_err(|e: ParseIntError| io::Error::new(io::ErrorKind::Other, e.to_string()))
+    pub fn read_oid(&self, f: &mut (impl Read + ?Sized)) -> Result<Oid, ArchiveError> {
+        Ok(self.read_string(f)?.parse()?)
     }
 
-    pub fn read_offset(&self, f: &mut (impl Read + ?Sized)) -> io::Result<Offset> {
+    pub fn read_offset(&self, f: &mut (impl Read + ?Sized)) -> Result<Offset, ArchiveError> {
         if self.offset_size == 0 {
-            return Err(io::Error::new(io::ErrorKind::Other, "offset size unknown"));
+            return Err(ArchiveError::InvalidData("offset size unknown".into()));
+        }
+        if self.offset_size > MAX_INT_SIZE {
+            return Err(ArchiveError::InvalidData("offset size too large".into()));
         }
 
-        let mut buffer = vec![0; self.offset_size + 1];
-        f.read_exact(buffer.as_mut_slice())?;
+        let mut buffer = [0; MAX_INT_SIZE + 1];
+        let buffer = &mut buffer[..=self.offset_size];
+        f.read_exact(buffer)?;
 
         match buffer[0] {
             0 => Ok(Offset::Unknown),
@@ -86,38 +125,237 @@ impl ReadConfig {
                 Ok(Offset::PosSet(offset))
             }
             3 => Ok(Offset::NoData),
-            _ => Err(io::Error::new(io::ErrorKind::Other, "invalid offset type")),
+            flag => Err(ArchiveError::InvalidOffsetFlag(flag)),
+        }
+    }
+
+    pub fn write_byte(&self, f: &mut (impl Write + ?Sized), value: u8) -> Result<(), ArchiveError> {
+        Ok(f.write_all(&[value])?)
+    }
+
+    /// Write an integer as a sign byte followed by `int_size` bytes.
+    pub fn write_int(&self, f: &mut (impl Write + ?Sized), value: i64) -> Result<(), ArchiveError> {
+        if self.int_size == 0 || self.int_size > MAX_INT_SIZE {
+            return Err(ArchiveError::InvalidData(format!(
+                "integer size {} is not supported",
+                self.int_size
+            )));
         }
+        let magnitude = value.unsigned_abs();
+        if self.int_size < MAX_INT_SIZE && magnitude >> (self.int_size * 8) != 0 {
+            return Err(ArchiveError::InvalidData(format!(
+                "integer {} does not fit in {} bytes",
+                value, self.int_size
+            )));
+        }
+        let mut buffer = [0; MAX_INT_SIZE + 1];
+        buffer[0] = u8::from(value < 0);
+        buffer[1..].copy_from_slice(&magnitude.to_le_bytes());
+        Ok(f.write_all(&buffer[..=self.int_size])?)
     }
 
-    pub fn read_data(&self, f: &mut File, o: Offset) -> Result<Box<dyn io::Read>, ArchiveError> {
-        match o {
-            Offset::NoData => Ok(Box::new(DataReader::empty(f.try_clone()?))),
-            Offset::PosNotSet => Err(ArchiveError::NoDataPresent),
-            Offset::Unknown => Err(ArchiveError::NoDataPresent),
+    pub fn write_string(
+        &self,
+        f: &mut (impl Write + ?Sized),
+        value: &str,
+    ) -> Result<(), ArchiveError> {
+        self.write_string_opt(f, Some(value))
+    }
+
+    /// Write a string, writing NULL for `None`.
+    pub fn write_string_opt(
+        &self,
+        f: &mut (impl Write + ?Sized),
+        value: Option<&str>,
+    ) -> Result<(), ArchiveError> {
+        match value {
+            Some(value) => {
+                let length = i64::try_from(value.len())
+                    .map_err(|_| ArchiveError::InvalidData("string is too long".into()))?;
+                self.write_int(f, length)?;
+                Ok(f.write_all(value.as_bytes())?)
+            }
+            None => self.write_int(f, -1),
+        }
+    }
+
+    pub fn write_offset(
+        &self,
+        f: &mut (impl Write + ?Sized),
+        offset: Offset,
+    ) -> Result<(), ArchiveError> {
+        if self.offset_size == 0 || self.offset_size > MAX_INT_SIZE {
+            return Err(ArchiveError::InvalidData(format!(
+                "offset size {} is not supported",
+                self.offset_size
+            )));
+        }
+        let (flag, value) = match offset {
+            Offset::Unknown => (0, 0),
+            Offset::PosNotSet => (1, 0),
+            Offset::PosSet(offset) => (2, offset),
+            Offset::NoData => (3, 0),
+        };
+        if self.offset_size < MAX_INT_SIZE && value >> (self.offset_size * 8) != 0 {
+            return Err(ArchiveError::InvalidData(format!(
+                "offset {} does not fit in {} bytes",
+                value, self.offset_size
+            )));
+        }
+        let mut buffer = [0; MAX_INT_SIZE + 1];
+        buffer[0] = flag;
+        buffer[1..].copy_from_slice(&value.to_le_bytes());
+        Ok(f.write_all(&buffer[..=self.offset_size])?)
+    }
+
+    /// Return a reader for the data blocks of a TOC entry.
+    ///
+    /// `end` is the position of the next data block in the file, if any.
+    pub fn data_reader<R: Read + Seek>(
+        &self,
+        mut f: R,
+        entry: &TocEntry,
+        end: Option<u64>,
+    ) -> Result<DataReader<R>, ArchiveError> {
+        match entry.offset {
+            Offset::NoData => Ok(DataReader::empty(f)),
+            Offset::PosNotSet | Offset::Unknown => Err(ArchiveError::no_data(entry)),
             Offset::PosSet(offset) => {
                 f.seek(io::SeekFrom::Start(offset))?;
                 let block_type: BlockType = self
-                    .read_byte(f)?
+                    .read_byte(&mut f)?
                     .try_into()
                     .or(Err(ArchiveError::InvalidData("invalid block type".into())))?;
-                let _id = self.read_int(f)?;
+                // There is no per-block compression flag: compression is set
+                // for the whole archive in the header.
+                let _id = self.read_int(&mut f)?;
                 match block_type {
                     BlockType::Blob => Err(ArchiveError::BlobNotSupported),
-                    BlockType::Data => Ok(Box::new(DataReader::new(f.try_clone()?, self.int_size))),
+                    BlockType::Data => {
+                        let reader = DataReader::new_seekable(f, self.int_size)?;
+                        Ok(match end {
+                            Some(end) => reader.with_end(end),
+                            None => reader,
+                        })
+                    }
                 }
             }
         }
     }
 }
 
+/// Reader that tracks the current position and the field being read, so
+/// parse errors can report where they occurred.
+#[derive(Debug)]
+pub struct CountingReader<R> {
+    inner: R,
+    position: u64,
+    field: &'static str,
+    field_start: u64,
+    warnings: Vec<ParseWarning>,
+}
+
+impl<R: Read> CountingReader<R> {
+    pub fn new(inner: R) -> CountingReader<R> {
+        CountingReader {
+            inner,
+            position: 0,
+            field: "",
+            field_start: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Create a reader for data which starts at `position` in the file.
+    pub fn with_position(inner: R, position: u64) -> CountingReader<R> {
+        CountingReader {
+            position,
+            field_start: position,
+            ..CountingReader::new(inner)
+        }
+    }
+
+    /// Return the number of bytes read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Mark the start of a new field at the current position.
+    pub fn start_field(&mut self, field: &'static str) {
+        self.field = field;
+        self.field_start = self.position;
+    }
+
+    /// Return the name of the field being read.
+    pub fn field(&self) -> &'static str {
+        self.field
+    }
+
+    /// Record a warning for the current field.
+    pub fn warn(&mut self, kind: WarningKind, message: String) {
+        #[cfg(feature = "tracing")]
+        tracing::warn!(offset = self.field_start, "{}", message);
+        self.warnings.push(ParseWarning {
+            kind,
+            message,
+            offset: self.field_start,
+        });
+    }
+
+    /// Return the warnings recorded so far, and clear them.
+    pub fn take_warnings(&mut self) -> Vec<ParseWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    /// Fill in the name of the current field for errors that include it.
+    pub fn add_field(&self, e: ArchiveError) -> ArchiveError {
+        match e {
+            ArchiveError::StringNotUtf8 {
+                field: None,
+                source,
+            } => ArchiveError::StringNotUtf8 {
+                field: Some(self.field).filter(|field| !field.is_empty()),
+                source,
+            },
+            e => e,
+        }
+    }
+
+    /// Wrap an error in an [`ArchiveError::ParseError`] for the current field.
+    ///
+    /// Errors which already have position information, or which are not
+    /// related to the data at a position, are returned unchanged.
+    pub fn wrap_error(&self, e: ArchiveError, context: String) -> ArchiveError {
+        match e {
+            ArchiveError::ParseError { .. } | ArchiveError::UnsupportedVersionError(_) => e,
+            e => ArchiveError::ParseError {
+                offset: self.field_start,
+                context,
+                source: Box::new(self.add_field(e)),
+            },
+        }
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
 fn read_int(f: &mut (impl Read + ?Sized), int_size: usize) -> io::Result<i64> {
     if int_size == 0 {
-        return Err(io::Error::new(io::ErrorKind::Other, "integer size unknown"));
+        return Err(io::Error::other("integer size unknown"));
+    }
+    if int_size > MAX_INT_SIZE {
+        return Err(io::Error::other("integer size too large"));
     }
 
-    let mut buffer = vec![0; int_size + 1];
-    f.read_exact(buffer.as_mut_slice())?;
+    let mut buffer = [0; MAX_INT_SIZE + 1];
+    let buffer = &mut buffer[..=int_size];
+    f.read_exact(buffer)?;
     let is_negative = buffer[0] != 0;
     let mut result: i64 = 0;
 
@@ -125,14 +363,38 @@ fn read_int(f: &mut (impl Read + ?Sized), int_size: usize) -> io::Result<i64> {
         result += (buffer[i + 1] as i64) << (i * 8);
     }
 
-    Ok(if is_negative { -result } else { result })
+    if is_negative {
+        result
+            .checked_neg()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "integer out of range"))
+    } else {
+        Ok(result)
+    }
 }
 
+/// Reader for the data blocks of a TOC entry.
+///
+/// pg_dump writes data as a sequence of chunks, each preceded by its length.
+/// This reader returns the contents of the chunks, without decompressing them.
+/// An [`UnexpectedEof`](io::ErrorKind::UnexpectedEof) error is returned if
+/// a chunk is shorter than its length. The length of the uncompressed data is
+/// not stored.
+///
+/// The data ends with a chunk of length zero. If the position of the next
+/// data block is known, an [`InvalidData`](io::ErrorKind::InvalidData) error
+/// is returned when the data does not end before it, instead of reading the
+/// next block as part of the data.
 #[derive(Debug)]
 pub struct DataReader<T: Read> {
     int_size: usize,
     inner: std::io::Take<T>,
     eof: bool,
+    /// Position of the first chunk, if known.
+    start: Option<u64>,
+    /// Size of the data including the chunk lengths, if known.
+    size: Option<u64>,
+    /// Number of bytes left before the end of the data, if known.
+    remaining: Option<u64>,
 }
 
 impl<T: Read> DataReader<T> {
@@ -141,6 +403,9 @@ impl<T: Read> DataReader<T> {
             int_size,
             inner: fd.take(0),
             eof: false,
+            start: None,
+            size: None,
+            remaining: None,
         }
     }
 
@@ -149,26 +414,152 @@ impl<T: Read> DataReader<T> {
             int_size: 0,
             inner: fd.take(0),
             eof: true,
+            start: None,
+            size: None,
+            remaining: None,
         }
     }
+
+    /// Return the underlying reader.
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
 }
-impl<T: Read> Read for DataReader<T> {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if self.eof {
-            return Ok(0);
+
+impl<T: Read + Seek> DataReader<T> {
+    /// Create a reader for data starting at the current position of `fd`.
+    ///
+    /// Unlike [`DataReader::new`] this remembers the start position, so it
+    /// can check that the data ends before the next data block.
+    pub fn new_seekable(mut fd: T, int_size: usize) -> io::Result<DataReader<T>> {
+        let start = fd.stream_position()?;
+        Ok(DataReader {
+            start: Some(start),
+            ..DataReader::new(fd, int_size)
+        })
+    }
+
+    /// Fail if the data does not end before position `end`, which is where
+    /// the next data block starts.
+    pub(crate) fn with_end(mut self, end: u64) -> Self {
+        if let Some(start) = self.start {
+            self.size = Some(end.saturating_sub(start));
+            self.remaining = self.size;
         }
+        self
+    }
 
+    /// Skip the remaining data without reading it.
+    ///
+    /// Returns the number of bytes skipped, which is the size of the data
+    /// as stored in the archive.
+    pub fn skip(&mut self) -> io::Result<u64> {
+        let mut size = 0;
+        loop {
+            let remaining = self.inner.limit();
+            if remaining > 0 {
+                self.inner
+                    .get_mut()
+                    .seek(io::SeekFrom::Current(remaining as i64))?;
+                self.inner.set_limit(0);
+                size += remaining;
+            }
+            if !self.next_chunk()? {
+                return Ok(size);
+            }
+        }
+    }
+}
+
+impl<T: Read> DataReader<T> {
+    /// Copy the chunks as stored, including their lengths and the zero length
+    /// which ends the data.
+    ///
+    /// This must be called before any data has been read. Returns the number
+    /// of bytes written.
+    pub(crate) fn copy_raw(&mut self, dst: &mut (impl Write + ?Sized)) -> io::Result<u64> {
+        let mut written = 0;
+        while !self.eof {
+            let mut buffer = [0; MAX_INT_SIZE + 1];
+            let buffer = &mut buffer[..=self.int_size];
+            self.consume(buffer.len() as u64)?;
+            self.inner.get_mut().read_exact(buffer)?;
+            let l = read_int(&mut &buffer[..], self.int_size)?;
+            if l < 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid chunk length",
+                ));
+            }
+            self.consume(l as u64)?;
+            dst.write_all(buffer)?;
+            written += buffer.len() as u64;
+            if l == 0 {
+                self.eof = true;
+                break;
+            }
+            let n = io::copy(&mut self.inner.get_mut().take(l as u64), dst)?;
+            if n < l as u64 {
+                // The file ended in the middle of a chunk.
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            written += n;
+        }
+        Ok(written)
+    }
+
+    /// Start reading the next chunk, if the current chunk has been read.
+    ///
+    /// Returns false at the end of the data.
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
         if self.inner.limit() == 0 {
+            self.consume((self.int_size + 1) as u64)?;
             self.inner.set_limit((self.int_size + 1) as u64);
             let l = read_int(&mut self.inner, self.int_size)?;
             if l == 0 {
                 self.eof = true;
-                return Ok(0);
+                return Ok(false);
             }
+            if l < 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "invalid chunk length",
+                ));
+            }
+            self.consume(l as u64)?;
             self.inner.set_limit(l as u64);
         }
+        Ok(true)
+    }
 
-        self.inner.read(buf)
+    /// Account for `n` bytes of data, failing if that passes the end of the
+    /// data.
+    fn consume(&mut self, n: u64) -> io::Result<()> {
+        if let Some(remaining) = &mut self.remaining {
+            *remaining = remaining.checked_sub(n).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "data does not end before the next data block",
+                )
+            })?;
+        }
+        Ok(())
+    }
+}
+impl<T: Read> Read for DataReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.next_chunk()? {
+            return Ok(0);
+        }
+        let n = self.inner.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            // The file ended in the middle of a chunk.
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(n)
     }
 }
 
@@ -177,7 +568,7 @@ mod tests {
     use super::*;
 
     #[test]
-    fn read_byte() -> Result<(), io::Error> {
+    fn read_byte() -> Result<(), ArchiveError> {
         let cfg = ReadConfig::new();
 
         // valid
@@ -191,7 +582,7 @@ mod tests {
     }
 
     #[test]
-    fn read_int() -> Result<(), io::Error> {
+    fn read_int() -> Result<(), ArchiveError> {
         let mut cfg = ReadConfig::new();
 
         // no int_size set
@@ -211,11 +602,21 @@ mod tests {
         input = b"\x00";
         assert!(cfg.read_int(&mut input).is_err());
 
+        // the magnitude of i64::MIN does not fit
+        cfg.int_size = 8;
+        input = b"\x01\x00\x00\x00\x00\x00\x00\x00\x80";
+        assert!(cfg.read_int(&mut input).is_err());
+
+        // integers larger than 8 bytes are not supported
+        cfg.int_size = 9;
+        input = &[0; 10];
+        assert!(cfg.read_int(&mut input).is_err());
+
         Ok(())
     }
 
     #[test]
-    fn read_string() -> Result<(), io::Error> {
+    fn read_string() -> Result<(), ArchiveError> {
         let mut cfg: ReadConfig = ReadConfig::new();
 
         // no int_size set
@@ -239,11 +640,55 @@ mod tests {
         input = b"\x00";
         assert!(cfg.read_string(&mut input).is_err());
 
+        // invalid UTF-8, outside of a known field
+        input = b"\x00\x01\x00\xff";
+        let e = cfg.read_string(&mut input).unwrap_err();
+        assert!(matches!(e, ArchiveError::StringNotUtf8 { field: None, .. }));
+        assert_eq!(e.to_string(), "string is not valid UTF-8");
+
         Ok(())
     }
 
     #[test]
-    fn read_int_bool() -> Result<(), io::Error> {
+    fn read_string_opt() -> Result<(), ArchiveError> {
+        let cfg = ReadConfig {
+            int_size: 2,
+            offset_size: 0,
+            ..ReadConfig::new()
+        };
+
+        // NULL
+        let mut input: &[u8] = b"\x01\x01\x00";
+        assert_eq!(cfg.read_string_opt(&mut input)?, None);
+
+        // empty string
+        input = b"\x00\x00\x00";
+        assert_eq!(cfg.read_string_opt(&mut input)?, Some(String::new()));
+
+        // valid string
+        input = b"\x00\x05\x00hello";
+        assert_eq!(cfg.read_string_opt(&mut input)?, Some("hello".into()));
+
+        // negative length
+        input = b"\x01\x02\x00";
+        assert!(cfg.read_string_opt(&mut input).is_err());
+
+        // too long
+        let cfg = ReadConfig {
+            max_string_length: 4,
+            ..cfg
+        };
+        input = b"\x00\x05\x00hello";
+        assert!(matches!(
+            cfg.read_string_opt(&mut input),
+            Err(ArchiveError::StringTooLong { length: 5, max: 4 })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn read_int_bool() -> Result<(), ArchiveError> {
         let mut cfg: ReadConfig = ReadConfig::new();
 
         // no int_size set
@@ -253,15 +698,15 @@ mod tests {
         // postive value
         cfg.int_size = 2;
         input = b"\x01\x01\x00";
-        assert_eq!(cfg.read_int_bool(&mut input)?, true);
+        assert!(cfg.read_int_bool(&mut input)?);
 
         // negative value
         input = b"\x01\x02\x00";
-        assert_eq!(cfg.read_int_bool(&mut input)?, true);
+        assert!(cfg.read_int_bool(&mut input)?);
 
         // zero is false
         input = b"\x00\x00\x00";
-        assert_eq!(cfg.read_int_bool(&mut input)?, false);
+        assert!(!cfg.read_int_bool(&mut input)?);
 
         // not enough data
         input = b"\x00";
@@ -271,7 +716,7 @@ mod tests {
     }
 
     #[test]
-    fn read_string_bool() -> Result<(), io::Error> {
+    fn read_string_bool() -> Result<(), ArchiveError> {
         let mut cfg: ReadConfig = ReadConfig::new();
 
         // no int_size set
@@ -281,15 +726,15 @@ mod tests {
         // true
         cfg.int_size = 2;
         input = b"\x00\x04\x00true";
-        assert_eq!(cfg.read_string_bool(&mut input)?, true);
+        assert!(cfg.read_string_bool(&mut input)?);
 
         // false
         input = b"\x00\x05\x00false";
-        assert_eq!(cfg.read_string_bool(&mut input)?, false);
+        assert!(!cfg.read_string_bool(&mut input)?);
 
         // other text
         input = b"\x00\x04\x00oops";
-        assert_eq!(cfg.read_string_bool(&mut input)?, false);
+        assert!(!cfg.read_string_bool(&mut input)?);
 
         // not enough data
         input = b"\x00";
@@ -299,7 +744,7 @@ mod tests {
     }
 
     #[test]
-    fn read_oid() -> Result<(), io::Error> {
+    fn read_oid() -> Result<(), ArchiveError> {
         let mut cfg: ReadConfig = ReadConfig::new();
 
         // no int_size set
@@ -309,7 +754,7 @@ mod tests {
         // positive number
         cfg.int_size = 2;
         input = b"\x00\x04\x001234";
-        assert_eq!(cfg.read_oid(&mut input)?, 1234);
+        assert_eq!(cfg.read_oid(&mut input)?, Oid(1234));
 
         // negative number
         input = b"\x00\x05\x00-1234";
@@ -319,6 +764,13 @@ mod tests {
         input = b"\x00\x05\x00x1234";
         assert!(cfg.read_oid(&mut input).is_err());
 
+        // larger than 32 bits
+        input = b"\x00\x0a\x004294967296";
+        assert!(matches!(
+            cfg.read_oid(&mut input),
+            Err(ArchiveError::InvalidOid(_))
+        ));
+
         // not enough data
         input = b"\x00";
         assert!(cfg.read_oid(&mut input).is_err());
@@ -327,7 +779,36 @@ mod tests {
     }
 
     #[test]
-    fn read_offset() -> Result<(), io::Error> {
+    fn counting_reader() -> Result<(), io::Error> {
+        let mut input: &[u8] = b"\x00\x01\x02\x03\x04";
+        let mut reader = CountingReader::new(&mut input);
+        reader.start_field("first");
+        reader.read_exact(&mut [0; 2])?;
+        reader.start_field("second");
+        reader.read_exact(&mut [0; 2])?;
+        assert_eq!(reader.field(), "second");
+
+        match reader.wrap_error(ArchiveError::InvalidData("oops".into()), "ctx".into()) {
+            ArchiveError::ParseError {
+                offset, context, ..
+            } => {
+                assert_eq!(offset, 2);
+                assert_eq!(context, "ctx");
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert!(matches!(
+            reader.wrap_error(
+                ArchiveError::UnsupportedVersionError((1, 2, 3).into()),
+                "ctx".into()
+            ),
+            ArchiveError::UnsupportedVersionError(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn read_offset() -> Result<(), ArchiveError> {
         let mut cfg: ReadConfig = ReadConfig::new();
 
         // no offset_size set
@@ -357,4 +838,99 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn write_values() -> Result<(), ArchiveError> {
+        let cfg = ReadConfig {
+            int_size: 2,
+            offset_size: 2,
+            ..ReadConfig::new()
+        };
+        let mut output = Vec::new();
+        cfg.write_int(&mut output, -0x0201)?;
+        cfg.write_string(&mut output, "hello")?;
+        cfg.write_string_opt(&mut output, None)?;
+        cfg.write_offset(&mut output, Offset::PosSet(513))?;
+        cfg.write_offset(&mut output, Offset::NoData)?;
+        assert_eq!(
+            output,
+            b"\x01\x01\x02\x00\x05\x00hello\x01\x01\x00\x02\x01\x02\x03\x00\x00"
+        );
+
+        let mut input = &output[..];
+        assert_eq!(cfg.read_int(&mut input)?, -0x0201);
+        assert_eq!(cfg.read_string(&mut input)?, "hello");
+        assert_eq!(cfg.read_string_opt(&mut input)?, None);
+        assert_eq!(cfg.read_offset(&mut input)?, Offset::PosSet(513));
+        assert_eq!(cfg.read_offset(&mut input)?, Offset::NoData);
+
+        // values which do not fit
+        assert!(cfg.write_int(&mut output, 0x10000).is_err());
+        assert!(cfg
+            .write_offset(&mut output, Offset::PosSet(0x10000))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn data_reader_chunk_length() {
+        // Chunk lengths match the data.
+        let data = b"\x00\x03\x00\x00\x00abc\x00\x02\x00\x00\x00de\x00\x00\x00\x00\x00";
+        let mut buffer = Vec::new();
+        DataReader::new(&data[..], 4)
+            .read_to_end(&mut buffer)
+            .unwrap();
+        assert_eq!(buffer, b"abcde");
+
+        // The second chunk declares more data than is present.
+        let data = b"\x00\x03\x00\x00\x00abc\x00\x05\x00\x00\x00de";
+        let mut buffer = Vec::new();
+        let err = DataReader::new(&data[..], 4)
+            .read_to_end(&mut buffer)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(buffer, b"abcde");
+    }
+
+    #[test]
+    fn data_reader_end() -> Result<(), io::Error> {
+        // The first chunk is followed by the data of another entry instead of
+        // a zero length.
+        let data = b"\x00\x03\x00\x00\x00abc\x00\x02\x00\x00\x00de\x00\x00\x00\x00\x00";
+        let mut buffer = Vec::new();
+        DataReader::new_seekable(io::Cursor::new(&data[..]), 4)?.read_to_end(&mut buffer)?;
+        assert_eq!(buffer, b"abcde");
+
+        let mut buffer = Vec::new();
+        let mut reader = DataReader::new_seekable(io::Cursor::new(&data[..]), 4)?.with_end(8);
+        let err = reader.read_to_end(&mut buffer).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(buffer, b"abc");
+        let mut reader = DataReader::new_seekable(io::Cursor::new(&data[..]), 4)?.with_end(8);
+        assert!(reader.skip().is_err());
+        let mut reader = DataReader::new_seekable(io::Cursor::new(&data[..]), 4)?.with_end(8);
+        assert!(reader.copy_raw(&mut Vec::new()).is_err());
+
+        // Data which ends exactly at the end is read completely.
+        let mut reader = DataReader::new_seekable(io::Cursor::new(&data[..]), 4)?.with_end(22);
+        assert_eq!(reader.skip()?, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn data_reader_copy_raw() {
+        // Data of the next entry follows the terminating zero length.
+        let data = b"\x00\x03\x00\x00\x00abc\x00\x02\x00\x00\x00de\x00\x00\x00\x00\x00\xff";
+        let mut output = Vec::new();
+        let mut reader = DataReader::new(&data[..], 4);
+        assert_eq!(reader.copy_raw(&mut output).unwrap(), 20);
+        assert_eq!(output, &data[..20]);
+        assert_eq!(reader.read(&mut [0; 1]).unwrap(), 0);
+
+        let data = b"\x00\x03\x00\x00\x00abc\x00\x05\x00\x00\x00de";
+        let err = DataReader::new(&data[..], 4)
+            .copy_raw(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
 }
@@ -0,0 +1,76 @@
+use crate::archive::Archive;
+use crate::header::Header;
+use crate::options::ParseOptions;
+use crate::toc::{Toc, TocEntry};
+use crate::types::{ArchiveError, ParseWarning, Section};
+use std::io::Read;
+
+/// An archive for which the table of contents is read on first use.
+///
+/// This is returned by [`Archive::parse_lazy`]. Reading the table of contents
+/// requires the reader, so the methods that need it take `&mut self` instead
+/// of using interior mutability. The table of contents is read only once;
+/// later calls use the cached entries.
+#[derive(Debug)]
+pub struct LazyArchive<R> {
+    header: Header,
+    reader: R,
+    options: ParseOptions,
+    toc: Option<Toc>,
+    toc_warnings: Vec<ParseWarning>,
+}
+
+impl<R: Read> LazyArchive<R> {
+    pub(crate) fn new(header: Header, reader: R, options: ParseOptions) -> LazyArchive<R> {
+        LazyArchive {
+            header,
+            reader,
+            options,
+            toc: None,
+            toc_warnings: Vec::new(),
+        }
+    }
+
+    /// Return the archive header. This never reads the table of contents.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Return the table of contents, reading it if that has not been done yet.
+    ///
+    /// If reading the table of contents fails the reader is left at an
+    /// unknown position, so the archive should not be used anymore.
+    pub fn toc_entries(&mut self) -> Result<&[TocEntry], ArchiveError> {
+        if self.toc.is_none() {
+            let (toc, warnings) = self.header.read_toc(&mut self.reader, &self.options)?;
+            self.toc = Some(toc);
+            self.toc_warnings = warnings;
+        }
+        Ok(self.toc.as_ref().map_or(&[], |toc| toc.entries.as_slice()))
+    }
+
+    /// Find a TOC entry by name and section.
+    ///
+    /// See [`Archive::find_toc_entry`].
+    pub fn find_toc_entry(
+        &mut self,
+        section: Section,
+        desc: &str,
+        tag: &str,
+    ) -> Result<Option<&TocEntry>, ArchiveError> {
+        Ok(self
+            .toc_entries()?
+            .iter()
+            .find(|e| e.section == section && e.desc == desc && e.tag == tag))
+    }
+
+    /// Convert into a fully parsed [`Archive`], reading the table of contents
+    /// if needed.
+    pub fn into_archive(mut self) -> Result<Archive, ArchiveError> {
+        self.toc_entries()?;
+        let toc = self.toc.unwrap_or_default();
+        let mut archive = Archive::from_header(self.header, toc);
+        archive.warnings.extend(self.toc_warnings);
+        Ok(archive)
+    }
+}
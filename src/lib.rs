@@ -6,21 +6,93 @@
 //! trust the SQL statements embedded in the dump, or if you want to
 //! process data without loading it into a database.
 //!
+//! Archives in tar format (`pg_dump -Ft`) can be read using
+//! [`Archive::parse_tar`].
+//!
 //! ```rust
-//! use std::fs::File;
 //! use pgarchive::Archive;
 //!
-//! let mut file = File::open("tests/test.pgdump").unwrap();
-//! match Archive::parse(&mut file) {
-//!     Ok(archive) => println!("This is a backup of {}", archive.database_name),
+//! match Archive::open("tests/test.pgdump") {
+//!     Ok((archive, _file)) => println!("This is a backup of {}", archive.database_name),
 //!     Err(e) => println!("can not read file: {:?}", e),
 //! };
 //! ```
+//!
+//! Archives do not have to be files: any reader which implements `Read` and
+//! `Seek` can be used, for example to read an archive from memory:
+//!
+//! ```rust
+//! use pgarchive::{Archive, Section};
+//! use std::io::{Cursor, Read};
+//!
+//! # #[cfg(feature = "gzip")]
+//! # fn main() {
+//! let mut archive_data = Cursor::new(std::fs::read("tests/test.pgdump").unwrap());
+//! let archive = Archive::parse(&mut archive_data).unwrap();
+//! let entry = archive
+//!     .find_toc_entry(Section::Data, "TABLE DATA", "pizza")
+//!     .expect("no data for pizza table present");
+//! let mut data = String::new();
+//! archive
+//!     .read_data(&mut archive_data, entry)
+//!     .unwrap()
+//!     .read_to_string(&mut data)
+//!     .unwrap();
+//! # }
+//! # #[cfg(not(feature = "gzip"))]
+//! # fn main() {}
+//! ```
+//!
+//! Without the default features the crate builds for WebAssembly. Gzip
+//! compressed archives, as made by default by pg_dump, can still be read by
+//! enabling the pure Rust `gzip` feature:
+//!
+//! ```sh
+//! cargo build --target wasm32-unknown-unknown --no-default-features --features gzip
+//! ```
 mod archive;
+mod archive_file;
+mod builder;
+mod diff;
+mod header;
 mod io;
+mod lazy;
+mod options;
+mod schema;
+mod settings;
+mod sql;
+mod stats;
 mod toc;
 mod types;
+pub mod versions;
+mod write;
+
+/// Parser internals used by the fuzz targets.
+///
+/// This is only available with the `fuzzing` feature, and is not part of the
+/// stable API.
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing {
+    pub use crate::io::{DataReader, ReadConfig};
+}
 
-pub use archive::Archive;
-pub use toc::{TocEntry, ID};
-pub use types::{ArchiveError, CompressionMethod, Section, Version};
+pub use archive::{Archive, EntryReader};
+pub use archive_file::ArchiveFile;
+pub use builder::ArchiveBuilder;
+pub use diff::TocDiff;
+pub use header::{Header, RawTimestamp};
+pub use io::DataReader;
+pub use lazy::LazyArchive;
+pub use options::ParseOptions;
+pub use schema::{AclGrant, CheckConstraint, Comment, ExtensionInfo, IndexInfo, RoleUsage};
+pub use settings::SessionSettings;
+pub use stats::ArchiveStats;
+#[allow(deprecated)]
+pub use toc::ID;
+pub use toc::{DumpId, TocEntry};
+pub use types::{
+    ArchiveError, CompressionMethod, DataAvailability, DataFormat, ObjectType, Offset, Oid,
+    ParseOidError, ParseSectionError, ParseVersionError, ParseWarning, Section, Version,
+    WarningKind,
+};
@@ -1,16 +1,229 @@
-use pgarchive::Archive;
+use anyhow::Context;
+use pgarchive::{Archive, RoleUsage, Section};
+use std::collections::BTreeMap;
 use std::env;
+use std::ffi::OsString;
+use std::fmt;
 use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
+const USAGE: &str =
+    "usage: pgarchive [list [--section SECTION[,SECTION...]] | owners [--json]] FILE...";
 
-    for path in args.into_iter().skip(1) {
-        println!("Checking {}", path);
-        let mut file = File::open(path).unwrap();
-        match Archive::parse(&mut file) {
-            Ok(hdr) => println!("{:?}", hdr),
-            Err(e) => println!("can not read file: {:?}", e),
+/// Error for invalid command line arguments.
+#[derive(Debug)]
+struct UsageError(String);
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for UsageError {}
+
+fn usage_error(message: impl Into<String>) -> anyhow::Error {
+    UsageError(message.into()).into()
+}
+
+/// Open and parse an archive, adding the path to any error.
+fn open_archive(path: &Path) -> anyhow::Result<Archive> {
+    let file = File::open(path).with_context(|| format!("can not open {}", path.display()))?;
+    Archive::parse(&mut BufReader::new(file))
+        .with_context(|| format!("can not read {}", path.display()))
+}
+
+/// Report an error for a single file.
+fn report(e: anyhow::Error) {
+    eprintln!("pgarchive: {:#}", e);
+}
+
+/// Return the exit code for a command that processed several files.
+fn exit_code(failed: bool) -> ExitCode {
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn parse_sections(value: &str) -> anyhow::Result<Vec<Section>> {
+    value
+        .split(',')
+        .map(|s| s.trim().parse().map_err(|e| usage_error(format!("{}", e))))
+        .collect()
+}
+
+fn require_paths(paths: &[PathBuf]) -> anyhow::Result<()> {
+    if paths.is_empty() {
+        return Err(usage_error("no archive files given"));
+    }
+    Ok(())
+}
+
+fn list(mut args: impl Iterator<Item = OsString>) -> anyhow::Result<ExitCode> {
+    let mut sections: Option<Vec<Section>> = None;
+    let mut paths = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let value = match arg.to_str() {
+            Some("--section") => args
+                .next()
+                .and_then(|v| v.into_string().ok())
+                .ok_or_else(|| usage_error("--section requires a value"))?,
+            Some(a) if a.starts_with("--section=") => a["--section=".len()..].to_string(),
+            _ => {
+                paths.push(PathBuf::from(arg));
+                continue;
+            }
+        };
+        sections
+            .get_or_insert_with(Vec::new)
+            .extend(parse_sections(&value)?);
+    }
+    require_paths(&paths)?;
+
+    let mut failed = false;
+    for path in paths {
+        let archive = match open_archive(&path) {
+            Ok(archive) => archive,
+            Err(e) => {
+                report(e);
+                failed = true;
+                continue;
+            }
         };
+        for entry in archive
+            .toc_entries
+            .iter()
+            .filter(|e| sections.as_ref().is_none_or(|s| s.contains(&e.section)))
+        {
+            println!(
+                "{}; {} {} {} {} {}",
+                entry.id, entry.section, entry.desc, entry.namespace, entry.tag, entry.owner
+            );
+        }
+    }
+    Ok(exit_code(failed))
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn owners(args: impl Iterator<Item = OsString>) -> anyhow::Result<ExitCode> {
+    let mut json = false;
+    let mut paths = Vec::new();
+    for arg in args {
+        match arg.to_str() {
+            Some("--json") => json = true,
+            _ => paths.push(PathBuf::from(arg)),
+        }
+    }
+    require_paths(&paths)?;
+
+    let mut failed = false;
+    let mut roles: BTreeMap<String, RoleUsage> = BTreeMap::new();
+    for path in paths {
+        let archive = match open_archive(&path) {
+            Ok(archive) => archive,
+            Err(e) => {
+                report(e);
+                failed = true;
+                continue;
+            }
+        };
+        for usage in archive.roles() {
+            let role = roles.entry(usage.name.clone()).or_insert(RoleUsage {
+                name: usage.name,
+                ..Default::default()
+            });
+            role.owned_objects += usage.owned_objects;
+            role.granted_privileges += usage.granted_privileges;
+        }
+    }
+
+    if json {
+        let items: Vec<String> = roles
+            .values()
+            .map(|r| {
+                format!(
+                    "{{\"role\": {}, \"owned_objects\": {}, \"granted_privileges\": {}}}",
+                    json_string(&r.name),
+                    r.owned_objects,
+                    r.granted_privileges
+                )
+            })
+            .collect();
+        println!("[{}]", items.join(", "));
+    } else {
+        println!("role\towned objects\tgranted privileges");
+        for role in roles.values() {
+            println!(
+                "{}\t{}\t{}",
+                role.name, role.owned_objects, role.granted_privileges
+            );
+        }
+    }
+    Ok(exit_code(failed))
+}
+
+fn show(args: impl Iterator<Item = OsString>) -> anyhow::Result<ExitCode> {
+    let paths: Vec<PathBuf> = args.map(PathBuf::from).collect();
+    require_paths(&paths)?;
+
+    let mut failed = false;
+    for path in paths {
+        println!("Checking {}", path.display());
+        match open_archive(&path) {
+            Ok(archive) => println!("{:?}", archive),
+            Err(e) => {
+                report(e);
+                failed = true;
+            }
+        }
+    }
+    Ok(exit_code(failed))
+}
+
+fn run() -> anyhow::Result<ExitCode> {
+    let mut args = env::args_os().skip(1).peekable();
+
+    match args.peek().and_then(|a| a.to_str()) {
+        Some("list") => {
+            args.next();
+            list(args)
+        }
+        Some("owners") => {
+            args.next();
+            owners(args)
+        }
+        _ => show(args),
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) if e.is::<UsageError>() => {
+            eprintln!("pgarchive: {}\n{}", e, USAGE);
+            ExitCode::from(2)
+        }
+        Err(e) => {
+            report(e);
+            ExitCode::FAILURE
+        }
     }
 }
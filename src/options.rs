@@ -0,0 +1,72 @@
+/// Options to control how an archive is parsed.
+///
+/// The defaults accept every archive pg_dump can produce. Limits can be set
+/// to protect against corrupt or untrusted archives using excessive amounts
+/// of memory.
+///
+/// ```rust
+/// # use std::fs::File;
+/// use pgarchive::{Archive, ParseOptions};
+///
+/// # let mut file = File::open("tests/test.pgdump").unwrap();
+/// let options = ParseOptions::new().max_string_length(1024 * 1024);
+/// let archive = Archive::parse_with_options(&mut file, &options);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseOptions {
+    pub(crate) max_entries: usize,
+    pub(crate) max_string_length: usize,
+    pub(crate) lenient: bool,
+    /// Accept all newer formats, for reading only the header.
+    pub(crate) header_only: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParseOptions {
+    /// Return the default options, which do not limit anything.
+    pub fn new() -> ParseOptions {
+        ParseOptions {
+            max_entries: usize::MAX,
+            max_string_length: usize::MAX,
+            lenient: false,
+            header_only: false,
+        }
+    }
+
+    /// Stop parsing after `max` TOC entries.
+    pub fn max_entries(mut self, max: usize) -> Self {
+        self.max_entries = max;
+        self
+    }
+
+    /// Fail with [`ArchiveError::StringTooLong`](crate::ArchiveError::StringTooLong)
+    /// if a string in the header or table of contents is longer than `max`
+    /// bytes.
+    ///
+    /// The length is checked before any memory is allocated for the string.
+    pub fn max_string_length(mut self, max: usize) -> Self {
+        self.max_string_length = max;
+        self
+    }
+
+    /// Try to read archives with problems which are not fatal, instead of
+    /// failing.
+    ///
+    /// Each problem is recorded as a [`ParseWarning`](crate::ParseWarning),
+    /// which can be retrieved using [`Archive::warnings`](crate::Archive::warnings).
+    /// This allows archives in a newer format than supported by
+    /// this crate. These are read using the layout of the most recent
+    /// supported format, and parsing only fails if the data does not match
+    /// that. For an invalid creation date in the header
+    /// [`Archive::create_date`](crate::Archive::create_date) returns the Unix
+    /// epoch.
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+}
@@ -0,0 +1,536 @@
+use crate::sql;
+use crate::toc::DumpId;
+
+/// Information about an index, extracted from an `INDEX` TOC entry.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndexInfo {
+    /// ID of the TOC entry for the index.
+    pub id: DumpId,
+    /// Name of the index.
+    pub name: String,
+    /// Schema of the table the index belongs to.
+    pub namespace: String,
+    /// Name of the table the index belongs to.
+    pub table: String,
+    /// Whether this is a `UNIQUE` index.
+    pub unique: bool,
+    /// The index access method, for example `btree` or `gist`.
+    pub method: String,
+    /// The indexed columns or expressions, as SQL.
+    pub columns: String,
+    /// The predicate for a partial index, as SQL.
+    pub predicate: Option<String>,
+}
+
+impl IndexInfo {
+    /// Parse a `CREATE INDEX` statement as generated by pg_dump.
+    pub(crate) fn parse(id: DumpId, defn: &str) -> Option<IndexInfo> {
+        let s = sql::strip_keyword(defn, "CREATE")?;
+        let (unique, s) = match sql::strip_keyword(s, "UNIQUE") {
+            Some(s) => (true, s),
+            None => (false, s),
+        };
+        let s = sql::strip_keyword(s, "INDEX")?;
+        let (name, s) = sql::identifier(s)?;
+        let s = sql::strip_keyword(s, "ON")?;
+        let s = sql::strip_keyword(s, "ONLY").unwrap_or(s);
+        let (mut table, s) = sql::qualified_name(s)?;
+        let (method, s) = match sql::strip_keyword(s, "USING") {
+            Some(s) => sql::identifier(s)?,
+            None => (String::from("btree"), s),
+        };
+        let (columns, s) = sql::parenthesized(s)?;
+        let predicate = sql::find_keyword(s, "WHERE").map(|i| {
+            s[i + "WHERE".len()..]
+                .trim()
+                .trim_end_matches(';')
+                .trim_end()
+                .to_string()
+        });
+
+        let table_name = table.pop()?;
+        Some(IndexInfo {
+            id,
+            name,
+            namespace: table.pop().unwrap_or_default(),
+            table: table_name,
+            unique,
+            method,
+            columns: columns.to_string(),
+            predicate,
+        })
+    }
+}
+
+/// A privilege grant, extracted from an `ACL` TOC entry.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AclGrant {
+    /// The granted privileges, for example `SELECT` or `ALL`.
+    pub privileges: Vec<String>,
+    /// The object the privileges are granted on, for example `TABLE public.pizza`.
+    pub object: String,
+    /// The role receiving the privileges. This is `PUBLIC` for privileges
+    /// granted to all roles.
+    pub grantee: String,
+    /// Whether the grantee may grant the privileges to other roles.
+    pub with_grant_option: bool,
+}
+
+impl AclGrant {
+    /// Extract all grants from the `GRANT` statements in a TOC entry definition.
+    ///
+    /// Other statements, such as `REVOKE`, are ignored.
+    pub(crate) fn parse_all(defn: &str) -> Vec<AclGrant> {
+        sql::statements(defn)
+            .into_iter()
+            .filter_map(AclGrant::parse_grant)
+            .flatten()
+            .collect()
+    }
+
+    fn parse_grant(stmt: &str) -> Option<Vec<AclGrant>> {
+        let s = sql::strip_keyword(stmt, "GRANT")?;
+        let on = sql::find_keyword(s, "ON")?;
+        let privileges: Vec<String> = sql::split_list(&s[..on])
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let s = &s[on + "ON".len()..];
+        let to = sql::find_keyword(s, "TO")?;
+        let object = s[..to].trim().to_string();
+
+        let mut grantees = Vec::new();
+        let mut s = &s[to + "TO".len()..];
+        loop {
+            let (grantee, rest) = sql::identifier(s)?;
+            grantees.push(grantee);
+            match rest.trim_start().strip_prefix(',') {
+                Some(rest) => s = rest,
+                None => {
+                    s = rest;
+                    break;
+                }
+            }
+        }
+        let with_grant_option = sql::strip_keyword(s, "WITH GRANT OPTION").is_some();
+
+        Some(
+            grantees
+                .into_iter()
+                .map(|grantee| AclGrant {
+                    privileges: privileges.clone(),
+                    object: object.clone(),
+                    grantee,
+                    with_grant_option,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// A `CHECK` constraint on a table.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CheckConstraint {
+    /// Name of the constraint. This is `None` for constraints defined without
+    /// a name.
+    pub name: Option<String>,
+    /// The column for a column constraint, or `None` for a table constraint.
+    pub column: Option<String>,
+    /// The checked expression, as SQL.
+    pub expression: String,
+    /// Whether the constraint is marked `NOT VALID`, so existing rows have
+    /// not been checked.
+    pub not_valid: bool,
+    /// Whether the constraint is marked `NO INHERIT`.
+    pub no_inherit: bool,
+}
+
+impl CheckConstraint {
+    /// Extract the check constraints from a `CREATE TABLE` statement.
+    pub(crate) fn parse_create_table(defn: &str) -> Vec<CheckConstraint> {
+        let elements = sql::statements(defn)
+            .into_iter()
+            .find_map(|stmt| {
+                let s = sql::strip_keyword(stmt, "CREATE")?;
+                let at = sql::find_keyword(s, "TABLE")?;
+                let (_, s) = sql::qualified_name(&s[at + "TABLE".len()..])?;
+                sql::parenthesized(s).map(|(elements, _)| elements)
+            })
+            .unwrap_or_default();
+        sql::split_list(elements)
+            .into_iter()
+            .filter_map(CheckConstraint::parse_table_element)
+            .collect()
+    }
+
+    /// Parse an `ALTER TABLE ... ADD CONSTRAINT ... CHECK` statement.
+    ///
+    /// Returns the name parts of the table and the constraint.
+    pub(crate) fn parse_alter_table(defn: &str) -> Option<(Vec<String>, CheckConstraint)> {
+        let s = sql::strip_keyword(defn, "ALTER TABLE")?;
+        let s = sql::strip_keyword(s, "ONLY").unwrap_or(s);
+        let (table, s) = sql::qualified_name(s)?;
+        let s = sql::strip_keyword(s, "ADD CONSTRAINT")?;
+        let (name, s) = sql::identifier(s)?;
+        let s = sql::strip_keyword(s, "CHECK")?;
+        Some((table, CheckConstraint::parse_check(Some(name), None, s)?))
+    }
+
+    fn parse_table_element(element: &str) -> Option<CheckConstraint> {
+        if let Some(s) = sql::strip_keyword(element, "CONSTRAINT") {
+            let (name, s) = sql::identifier(s)?;
+            let s = sql::strip_keyword(s, "CHECK")?;
+            return CheckConstraint::parse_check(Some(name), None, s);
+        }
+        if let Some(s) = sql::strip_keyword(element, "CHECK") {
+            return CheckConstraint::parse_check(None, None, s);
+        }
+
+        // A column definition, which may include a column constraint.
+        let (column, s) = sql::identifier(element)?;
+        let at = sql::find_keyword(s, "CHECK")?;
+        let name = sql::find_keyword(&s[..at], "CONSTRAINT")
+            .and_then(|i| sql::identifier(&s[i + "CONSTRAINT".len()..]))
+            .map(|(name, _)| name);
+        CheckConstraint::parse_check(name, Some(column), &s[at + "CHECK".len()..])
+    }
+
+    /// Parse the expression and options following the `CHECK` keyword.
+    fn parse_check(
+        name: Option<String>,
+        column: Option<String>,
+        s: &str,
+    ) -> Option<CheckConstraint> {
+        let (expression, rest) = sql::parenthesized(s)?;
+        Some(CheckConstraint {
+            name,
+            column,
+            expression: expression.to_string(),
+            not_valid: sql::find_keyword(rest, "NOT VALID").is_some(),
+            no_inherit: sql::find_keyword(rest, "NO INHERIT").is_some(),
+        })
+    }
+}
+
+/// A comment on a database object, extracted from a `COMMENT` TOC entry.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Comment {
+    /// The type of object, for example `TABLE`, `COLUMN` or `FUNCTION`.
+    pub object_type: String,
+    /// The object the comment is on, as SQL. For example `public.pizza` for a
+    /// table, or `public.add(a integer, b integer)` for a function.
+    pub object_name: String,
+    /// The comment text.
+    pub text: String,
+}
+
+impl Comment {
+    /// Parse a `COMMENT ON` statement as generated by pg_dump.
+    ///
+    /// pg_dump writes keywords in upper case and quotes identifiers which
+    /// are not in lower case, so the object type is the leading run of upper
+    /// case words.
+    pub(crate) fn parse(defn: &str) -> Option<Comment> {
+        let s = sql::strip_keyword(defn, "COMMENT ON")?.trim_start();
+        let is = sql::find_keyword(s, "IS")?;
+        let (object, text) = (s[..is].trim(), &s[is + "IS".len()..]);
+
+        let mut type_end = 0;
+        for word in object.split(' ') {
+            if word.is_empty() || !word.bytes().all(|b| b.is_ascii_uppercase()) {
+                break;
+            }
+            type_end += word.len() + 1;
+        }
+        let type_end = type_end.min(object.len());
+        let object_type = object[..type_end].trim_end();
+        if object_type.is_empty() {
+            return None;
+        }
+
+        let (text, _) = sql::string_literal(text)?;
+        Some(Comment {
+            object_type: object_type.to_string(),
+            object_name: object[type_end..].trim().to_string(),
+            text: text.replace("''", "'"),
+        })
+    }
+}
+
+/// An extension, extracted from an `EXTENSION` TOC entry.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ExtensionInfo {
+    /// Name of the extension.
+    pub name: String,
+    /// Schema the objects of the extension are created in.
+    pub schema: String,
+    /// Version of the extension. pg_dump does not include the version, so
+    /// this is normally `None` and the default version is installed on
+    /// restore.
+    pub version: Option<String>,
+}
+
+impl ExtensionInfo {
+    /// Parse a `CREATE EXTENSION` statement as generated by pg_dump.
+    pub(crate) fn parse(defn: &str) -> Option<ExtensionInfo> {
+        let s = sql::strip_keyword(defn, "CREATE EXTENSION")?;
+        let s = sql::strip_keyword(s, "IF NOT EXISTS").unwrap_or(s);
+        let (name, mut s) = sql::identifier(s)?;
+        s = sql::strip_keyword(s, "WITH").unwrap_or(s);
+
+        let mut schema = String::new();
+        let mut version = None;
+        loop {
+            if let Some(rest) = sql::strip_keyword(s, "SCHEMA") {
+                let (name, rest) = sql::identifier(rest)?;
+                schema = name;
+                s = rest;
+            } else if let Some(rest) = sql::strip_keyword(s, "VERSION") {
+                let (value, rest) = match sql::string_literal(rest) {
+                    Some((value, rest)) => (value.replace("''", "'"), rest),
+                    None => sql::identifier(rest)?,
+                };
+                version = Some(value);
+                s = rest;
+            } else if let Some(rest) = sql::strip_keyword(s, "CASCADE") {
+                s = rest;
+            } else {
+                break;
+            }
+        }
+
+        Some(ExtensionInfo {
+            name,
+            schema,
+            version,
+        })
+    }
+}
+
+/// Summary of how a role is used in an archive.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoleUsage {
+    /// Name of the role.
+    pub name: String,
+    /// Number of objects owned by the role.
+    pub owned_objects: usize,
+    /// Number of privileges granted to the role.
+    pub granted_privileges: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_comment() {
+        assert_eq!(
+            Comment::parse("COMMENT ON TABLE public.pizza IS 'All the pizza''s';\n"),
+            Some(Comment {
+                object_type: String::from("TABLE"),
+                object_name: String::from("public.pizza"),
+                text: String::from("All the pizza's"),
+            })
+        );
+        assert_eq!(
+            Comment::parse("COMMENT ON MATERIALIZED VIEW public.\"IS\" IS 'x';\n"),
+            Some(Comment {
+                object_type: String::from("MATERIALIZED VIEW"),
+                object_name: String::from("public.\"IS\""),
+                text: String::from("x"),
+            })
+        );
+        assert_eq!(
+            Comment::parse("COMMENT ON LARGE OBJECT 16384 IS 'logo';\n")
+                .map(|c| (c.object_type, c.object_name)),
+            Some((String::from("LARGE OBJECT"), String::from("16384")))
+        );
+        assert_eq!(
+            Comment::parse("COMMENT ON TABLE public.pizza IS NULL;"),
+            None
+        );
+        assert_eq!(
+            Comment::parse("GRANT ALL ON TABLE public.pizza TO x;"),
+            None
+        );
+    }
+
+    #[test]
+    fn parse_index() {
+        assert_eq!(
+            IndexInfo::parse(
+                DumpId(3442),
+                "CREATE INDEX pizza_name ON public.pizza USING btree (name);\n"
+            ),
+            Some(IndexInfo {
+                id: DumpId(3442),
+                name: String::from("pizza_name"),
+                namespace: String::from("public"),
+                table: String::from("pizza"),
+                unique: false,
+                method: String::from("btree"),
+                columns: String::from("name"),
+                predicate: None,
+            })
+        );
+    }
+
+    #[test]
+    fn parse_unique_partial_expression_index() {
+        let index = IndexInfo::parse(
+            DumpId(1),
+            "CREATE UNIQUE INDEX \"Lower Name\" ON ONLY public.\"Pizza\" USING btree (lower(name), (size * 2)) WHERE (deleted_at IS NULL);\n",
+        )
+        .unwrap();
+        assert_eq!(index.name, "Lower Name");
+        assert_eq!(index.namespace, "public");
+        assert_eq!(index.table, "Pizza");
+        assert!(index.unique);
+        assert_eq!(index.columns, "lower(name), (size * 2)");
+        assert_eq!(index.predicate.as_deref(), Some("(deleted_at IS NULL)"));
+    }
+
+    #[test]
+    fn parse_invalid_index() {
+        assert_eq!(IndexInfo::parse(DumpId(1), ""), None);
+        assert_eq!(IndexInfo::parse(DumpId(1), "CREATE TABLE pizza ();"), None);
+    }
+
+    #[test]
+    fn parse_empty_defn() {
+        for defn in ["", "  \n"] {
+            assert_eq!(IndexInfo::parse(DumpId(1), defn), None);
+            assert!(AclGrant::parse_all(defn).is_empty());
+            assert!(CheckConstraint::parse_create_table(defn).is_empty());
+            assert!(CheckConstraint::parse_alter_table(defn).is_none());
+            assert_eq!(Comment::parse(defn), None);
+        }
+    }
+
+    #[test]
+    fn parse_acl_grants() {
+        let grants = AclGrant::parse_all(
+            "REVOKE ALL ON TABLE public.pizza FROM PUBLIC;\n\
+             GRANT SELECT,INSERT ON TABLE public.pizza TO alice;\n\
+             GRANT SELECT(name) ON TABLE public.pizza TO \"bob smith\", carol WITH GRANT OPTION;\n",
+        );
+        assert_eq!(
+            grants,
+            vec![
+                AclGrant {
+                    privileges: vec![String::from("SELECT"), String::from("INSERT")],
+                    object: String::from("TABLE public.pizza"),
+                    grantee: String::from("alice"),
+                    with_grant_option: false,
+                },
+                AclGrant {
+                    privileges: vec![String::from("SELECT(name)")],
+                    object: String::from("TABLE public.pizza"),
+                    grantee: String::from("bob smith"),
+                    with_grant_option: true,
+                },
+                AclGrant {
+                    privileges: vec![String::from("SELECT(name)")],
+                    object: String::from("TABLE public.pizza"),
+                    grantee: String::from("carol"),
+                    with_grant_option: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_create_table_checks() {
+        let checks = CheckConstraint::parse_create_table(
+            "CREATE TABLE public.pizza (\n    \
+                 id integer NOT NULL,\n    \
+                 price numeric,\n    \
+                 size integer CONSTRAINT positive CHECK ((size > 0)) NOT NULL,\n    \
+                 name text CHECK (name <> ''),\n    \
+                 CONSTRAINT ch CHECK ((id <> 7)) NO INHERIT,\n    \
+                 CONSTRAINT pizza_price_check CHECK ((price > (0)::numeric))\n\
+             );\n",
+        );
+        assert_eq!(
+            checks,
+            vec![
+                CheckConstraint {
+                    name: Some(String::from("positive")),
+                    column: Some(String::from("size")),
+                    expression: String::from("(size > 0)"),
+                    not_valid: false,
+                    no_inherit: false,
+                },
+                CheckConstraint {
+                    name: None,
+                    column: Some(String::from("name")),
+                    expression: String::from("name <> ''"),
+                    not_valid: false,
+                    no_inherit: false,
+                },
+                CheckConstraint {
+                    name: Some(String::from("ch")),
+                    column: None,
+                    expression: String::from("(id <> 7)"),
+                    not_valid: false,
+                    no_inherit: true,
+                },
+                CheckConstraint {
+                    name: Some(String::from("pizza_price_check")),
+                    column: None,
+                    expression: String::from("(price > (0)::numeric)"),
+                    not_valid: false,
+                    no_inherit: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_extension() {
+        assert_eq!(
+            ExtensionInfo::parse("CREATE EXTENSION IF NOT EXISTS postgis WITH SCHEMA public;\n"),
+            Some(ExtensionInfo {
+                name: String::from("postgis"),
+                schema: String::from("public"),
+                version: None,
+            })
+        );
+        assert_eq!(
+            ExtensionInfo::parse(
+                "CREATE EXTENSION \"uuid-ossp\" SCHEMA extensions VERSION '1.1' CASCADE;"
+            ),
+            Some(ExtensionInfo {
+                name: String::from("uuid-ossp"),
+                schema: String::from("extensions"),
+                version: Some(String::from("1.1")),
+            })
+        );
+        assert_eq!(ExtensionInfo::parse("DROP EXTENSION postgis;"), None);
+    }
+
+    #[test]
+    fn parse_alter_table_check() {
+        let (table, check) = CheckConstraint::parse_alter_table(
+            "ALTER TABLE public.pizza\n    ADD CONSTRAINT name_len CHECK ((length(name) < 100)) NOT VALID;\n",
+        )
+        .unwrap();
+        assert_eq!(table, vec![String::from("public"), String::from("pizza")]);
+        assert_eq!(check.name.as_deref(), Some("name_len"));
+        assert_eq!(check.expression, "(length(name) < 100)");
+        assert!(check.not_valid);
+
+        assert_eq!(
+            CheckConstraint::parse_alter_table(
+                "ALTER TABLE ONLY public.pizza\n    ADD CONSTRAINT pizza_pkey PRIMARY KEY (id);\n"
+            ),
+            None
+        );
+    }
+}
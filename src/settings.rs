@@ -0,0 +1,217 @@
+use crate::archive::Archive;
+use crate::sql;
+use crate::types::ObjectType;
+use std::collections::BTreeMap;
+
+/// Session settings made by an archive before any objects are restored.
+///
+/// pg_dump writes `ENCODING`, `STDSTRINGS` and `SEARCHPATH` entries, which
+/// configure the session used for the restore. These settings affect how the
+/// SQL in the other entries must be interpreted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionSettings {
+    /// The encoding of all strings in the archive, such as `UTF8`.
+    pub client_encoding: Option<String>,
+    /// Whether backslashes in ordinary string literals are treated literally.
+    pub standard_conforming_strings: Option<bool>,
+    /// The schemas in the search path. pg_dump sets an empty search path and
+    /// qualifies all names instead.
+    pub search_path: Option<Vec<String>>,
+    /// Other settings, with their value as SQL. Quotes around a single
+    /// string value are removed.
+    pub other: BTreeMap<String, String>,
+}
+
+impl Archive {
+    /// Return the session settings made by the archive.
+    ///
+    /// The settings are extracted from the `SET` statements and
+    /// `set_config()` calls of the `ENCODING`, `STDSTRINGS` and `SEARCHPATH`
+    /// entries.
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use pgarchive::Archive;
+    ///
+    /// let archive = Archive::parse(&mut File::open("tests/test.pgdump").unwrap()).unwrap();
+    /// let settings = archive.session_settings();
+    /// println!("client encoding: {:?}", settings.client_encoding);
+    /// ```
+    pub fn session_settings(&self) -> SessionSettings {
+        let mut settings = SessionSettings::default();
+        let entries = self.toc_entries.iter().filter(|e| {
+            matches!(
+                ObjectType::known(&e.desc),
+                Some(ObjectType::Encoding | ObjectType::StdStrings | ObjectType::SearchPath)
+            )
+        });
+        for entry in entries {
+            for stmt in sql::statements(&entry.defn) {
+                let Some((name, value)) = set_statement(stmt).or_else(|| set_config_call(stmt))
+                else {
+                    continue;
+                };
+                match name.to_ascii_lowercase().as_str() {
+                    "client_encoding" => settings.client_encoding = Some(value),
+                    "standard_conforming_strings" => {
+                        settings.standard_conforming_strings = parse_bool(&value)
+                    }
+                    "search_path" => settings.search_path = parse_search_path(&value),
+                    _ => {
+                        settings.other.insert(name, value);
+                    }
+                }
+            }
+        }
+        settings
+    }
+}
+
+/// Return the name and value of a `SET` statement.
+pub(crate) fn set_statement(stmt: &str) -> Option<(String, String)> {
+    let (name, value) = sql::set_statement(stmt)?;
+    let value = match sql::string_literal(value) {
+        Some((value, rest)) if rest.trim().is_empty() => value.replace("''", "'"),
+        _ => value.to_string(),
+    };
+    Some((name.to_string(), value))
+}
+
+/// Return the name and value of a `SELECT pg_catalog.set_config(...)`
+/// statement, which pg_dump uses to set the search path.
+fn set_config_call(stmt: &str) -> Option<(String, String)> {
+    let s = sql::strip_keyword(stmt, "SELECT")?.trim_start();
+    let s = s.strip_prefix("pg_catalog.").unwrap_or(s);
+    let (args, _) = sql::parenthesized(sql::strip_keyword(s, "set_config")?)?;
+    let args = sql::split_list(args);
+    let (name, _) = sql::string_literal(args.first()?)?;
+    let (value, _) = sql::string_literal(args.get(1)?)?;
+    Some((name.replace("''", "'"), value.replace("''", "'")))
+}
+
+/// Parse a boolean setting value.
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "on" | "true" | "yes" | "1" => Some(true),
+        "off" | "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a comma separated list of schema names.
+fn parse_search_path(value: &str) -> Option<Vec<String>> {
+    if value.trim().is_empty() {
+        return Some(Vec::new());
+    }
+    sql::split_list(value)
+        .into_iter()
+        .map(|item| match sql::string_literal(item) {
+            Some((name, "")) => Some(name.replace("''", "'")),
+            _ => match sql::identifier(item)? {
+                (name, "") => Some(name),
+                _ => None,
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::toc::{DumpId, TocEntry};
+    use crate::types::Section;
+
+    fn settings_entry(id: u32, desc: &str, defn: &str) -> TocEntry {
+        TocEntry {
+            defn: String::from(defn),
+            ..TocEntry::new(DumpId(id), Section::PreData, desc, desc)
+        }
+    }
+
+    fn session_settings(entries: Vec<TocEntry>) -> SessionSettings {
+        entries
+            .into_iter()
+            .fold(Archive::builder(), |builder, entry| {
+                builder.toc_entry(entry)
+            })
+            .build()
+            .session_settings()
+    }
+
+    #[test]
+    fn pg_dump_settings() {
+        let settings = session_settings(vec![
+            settings_entry(1, "ENCODING", "SET client_encoding = 'UTF8';\n"),
+            settings_entry(2, "STDSTRINGS", "SET standard_conforming_strings = 'on';\n"),
+            settings_entry(
+                3,
+                "SEARCHPATH",
+                "SELECT pg_catalog.set_config('search_path', '', false);\n",
+            ),
+        ]);
+        assert_eq!(
+            settings,
+            SessionSettings {
+                client_encoding: Some(String::from("UTF8")),
+                standard_conforming_strings: Some(true),
+                search_path: Some(vec![]),
+                other: BTreeMap::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn search_path_list() {
+        let settings = session_settings(vec![settings_entry(
+            1,
+            "SEARCHPATH",
+            "SELECT pg_catalog.set_config('search_path', '\"$user\", public, \"Pizza Shop\"', false);\n",
+        )]);
+        assert_eq!(
+            settings.search_path,
+            Some(vec![
+                String::from("$user"),
+                String::from("public"),
+                String::from("Pizza Shop"),
+            ])
+        );
+
+        let settings = session_settings(vec![settings_entry(
+            1,
+            "SEARCHPATH",
+            "SET search_path = pizza, pg_catalog;\n",
+        )]);
+        assert_eq!(
+            settings.search_path,
+            Some(vec![String::from("pizza"), String::from("pg_catalog")])
+        );
+    }
+
+    #[test]
+    fn other_settings() {
+        let settings = session_settings(vec![
+            settings_entry(
+                1,
+                "STDSTRINGS",
+                "SET standard_conforming_strings = off;\nSET escape_string_warning = off;\n",
+            ),
+            settings_entry(2, "SEARCHPATH", "SET row_security = off;\n"),
+        ]);
+        assert_eq!(settings.standard_conforming_strings, Some(false));
+        assert_eq!(settings.client_encoding, None);
+        assert_eq!(settings.search_path, None);
+        assert_eq!(
+            settings.other,
+            BTreeMap::from([
+                (String::from("escape_string_warning"), String::from("off")),
+                (String::from("row_security"), String::from("off")),
+            ])
+        );
+    }
+
+    #[test]
+    fn no_settings() {
+        assert_eq!(session_settings(vec![]), SessionSettings::default());
+    }
+}
@@ -0,0 +1,472 @@
+//! Helpers to pick apart SQL statements stored in TOC entries.
+//!
+//! This is not a general SQL parser: it only understands the small subset of
+//! syntax that pg_dump generates for the statements this crate inspects.
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Strip a keyword (or sequence of keywords) from the start of a string.
+///
+/// Matching is case-insensitive and leading whitespace is ignored. Returns
+/// the remainder of the string after the keyword.
+pub(crate) fn strip_keyword<'a>(s: &'a str, keyword: &str) -> Option<&'a str> {
+    let s = s.trim_start();
+    let mut rest = s;
+    for word in keyword.split_whitespace() {
+        rest = rest.trim_start();
+        let head = rest.get(..word.len())?;
+        if !head.eq_ignore_ascii_case(word) {
+            return None;
+        }
+        rest = &rest[word.len()..];
+        if rest.starts_with(is_identifier_char) {
+            return None;
+        }
+    }
+    Some(rest)
+}
+
+/// Read a possibly quoted identifier from the start of a string.
+///
+/// Returns the identifier with quotes removed, and the remainder of the
+/// string.
+pub(crate) fn identifier(s: &str) -> Option<(String, &str)> {
+    let s = s.trim_start();
+    if let Some(quoted) = s.strip_prefix('"') {
+        let mut name = String::new();
+        let mut chars = quoted.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if c == '"' {
+                if quoted[i + 1..].starts_with('"') {
+                    chars.next();
+                } else {
+                    return Some((name, &quoted[i + 1..]));
+                }
+            }
+            name.push(c);
+        }
+        None
+    } else {
+        let end = s.find(|c| !is_identifier_char(c)).unwrap_or(s.len());
+        if end == 0 {
+            return None;
+        }
+        Some((s[..end].to_string(), &s[end..]))
+    }
+}
+
+/// Quote an identifier for use in a SQL statement.
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Format an identifier for use in a SQL statement, quoting it if needed.
+///
+/// Like pg_dump, names which only contain lowercase letters, digits and
+/// underscores are not quoted. Keywords are not detected.
+pub(crate) fn format_identifier(name: &str) -> String {
+    let plain = name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if plain {
+        name.to_string()
+    } else {
+        quote_identifier(name)
+    }
+}
+
+/// Read a string literal from the start of a string.
+///
+/// Returns the text between the quotes and the remainder of the string.
+/// Doubled quotes inside the literal are not unescaped.
+pub(crate) fn string_literal(s: &str) -> Option<(&str, &str)> {
+    let quoted = s.trim_start().strip_prefix('\'')?;
+    let mut chars = quoted.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '\'' {
+            if chars.peek().is_some_and(|&(_, c)| c == '\'') {
+                chars.next();
+            } else {
+                return Some((&quoted[..i], &quoted[i + 1..]));
+            }
+        }
+    }
+    None
+}
+
+/// Read a possibly schema-qualified name from the start of a string.
+///
+/// Returns the name parts and the remainder of the string.
+pub(crate) fn qualified_name(s: &str) -> Option<(Vec<String>, &str)> {
+    let (name, mut rest) = identifier(s)?;
+    let mut parts = vec![name];
+    while let Some(next) = rest.strip_prefix('.') {
+        let (name, r) = identifier(next)?;
+        parts.push(name);
+        rest = r;
+    }
+    Some((parts, rest))
+}
+
+/// Read a parenthesised expression from the start of a string.
+///
+/// Returns the text between the outer parentheses and the remainder of the
+/// string.
+pub(crate) fn parenthesized(s: &str) -> Option<(&str, &str)> {
+    let s = s.trim_start();
+    if !s.starts_with('(') {
+        return None;
+    }
+    let end = top_level_positions(s).find(|&(_, depth)| depth == 0)?.0;
+    Some((&s[1..end], &s[end + 1..]))
+}
+
+/// Find a keyword outside of parentheses, quoted identifiers and strings.
+///
+/// Returns the byte offset of the keyword.
+pub(crate) fn find_keyword(s: &str, keyword: &str) -> Option<usize> {
+    top_level_positions(s)
+        .filter(|&(_, depth)| depth == 0)
+        .map(|(i, _)| i)
+        .find(|&i| {
+            s[i..].starts_with(is_identifier_char)
+                && !s[..i].ends_with(is_identifier_char)
+                && strip_keyword(&s[i..], keyword).is_some()
+        })
+}
+
+/// Split a comma separated list.
+///
+/// Commas inside parentheses, quoted identifiers and strings are ignored.
+/// The list items are returned with surrounding whitespace removed.
+pub(crate) fn split_list(s: &str) -> Vec<&str> {
+    split_top_level(s, ',').into_iter().map(str::trim).collect()
+}
+
+/// Split SQL text into separate statements.
+///
+/// Statements are separated by semicolons outside of quoted identifiers and
+/// strings. Empty statements are skipped.
+pub(crate) fn statements(s: &str) -> Vec<&str> {
+    let mut result = split_top_level(s, ';');
+    result.retain(|stmt| !stmt.trim().is_empty());
+    result
+}
+
+/// Split a `SET name = value` or `SET name TO value` statement.
+///
+/// Returns the name of the setting and its value as SQL, without a trailing
+/// semicolon.
+pub(crate) fn set_statement(stmt: &str) -> Option<(&str, &str)> {
+    let s = strip_keyword(stmt, "SET")?.trim_start();
+    let end = s.find(|c: char| !is_identifier_char(c) && c != '.')?;
+    let (name, s) = s.split_at(end);
+    if name.is_empty() {
+        return None;
+    }
+    let s = s.trim_start();
+    let value = match s.strip_prefix('=') {
+        Some(s) => s,
+        None => strip_keyword(s, "TO")?,
+    };
+    let value = value.trim();
+    Some((name, value.strip_suffix(';').unwrap_or(value).trim_end()))
+}
+
+/// Apply a function to each statement in SQL text.
+///
+/// Separators and text between statements are kept as they are.
+pub(crate) fn map_statements(s: &str, f: impl FnMut(&str) -> String) -> String {
+    split_top_level(s, ';')
+        .into_iter()
+        .map(f)
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Rename the roles in the lists of names following any of `keywords`.
+///
+/// This is used for clauses such as `TO alice, bob` in a `GRANT` statement.
+/// Names which are not in `mapping` are left alone.
+pub(crate) fn rename_roles(
+    s: &str,
+    keywords: &[&str],
+    mapping: &std::collections::HashMap<String, String>,
+) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some((i, keyword)) = keywords
+        .iter()
+        .filter_map(|&keyword| find_keyword(rest, keyword).map(|i| (i, keyword)))
+        .min()
+    {
+        let mut list = strip_keyword(&rest[i..], keyword).unwrap_or_default();
+        result.push_str(&rest[..rest.len() - list.len()]);
+        while let Some((name, after)) = identifier(list) {
+            let start = list.len() - list.trim_start().len();
+            result.push_str(&list[..start]);
+            match mapping.get(&name) {
+                Some(new_name) => result.push_str(&format_identifier(new_name)),
+                None => result.push_str(&list[start..list.len() - after.len()]),
+            }
+            list = after;
+            let Some(next) = list.trim_start().strip_prefix(',') else {
+                break;
+            };
+            result.push_str(&list[..list.len() - next.len()]);
+            list = next;
+        }
+        rest = list;
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Rename the roles in a `GRANT`, `REVOKE`, `ALTER DEFAULT PRIVILEGES` or
+/// `SET SESSION AUTHORIZATION` statement.
+///
+/// Only the grantees, the `GRANTED BY` role, the `FOR ROLE` target and the
+/// session user are renamed. Other statements are returned unchanged.
+pub(crate) fn rename_acl_roles(
+    stmt: &str,
+    mapping: &std::collections::HashMap<String, String>,
+) -> String {
+    if strip_keyword(stmt, "SET SESSION AUTHORIZATION").is_some() {
+        rename_roles(stmt, &["SESSION AUTHORIZATION"], mapping)
+    } else if strip_keyword(stmt, "ALTER DEFAULT PRIVILEGES").is_some() {
+        let Some(i) = [find_keyword(stmt, "GRANT"), find_keyword(stmt, "REVOKE")]
+            .into_iter()
+            .flatten()
+            .min()
+        else {
+            return stmt.to_string();
+        };
+        let (head, tail) = stmt.split_at(i);
+        rename_roles(head, &["FOR ROLE", "FOR USER"], mapping) + &rename_acl_roles(tail, mapping)
+    } else if strip_keyword(stmt, "GRANT").is_some() {
+        rename_roles(stmt, &["TO", "GRANTED BY"], mapping)
+    } else if strip_keyword(stmt, "REVOKE").is_some() {
+        rename_roles(stmt, &["FROM", "GRANTED BY"], mapping)
+    } else {
+        stmt.to_string()
+    }
+}
+
+fn split_top_level(s: &str, separator: char) -> Vec<&str> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    for (i, _) in
+        top_level_positions(s).filter(|&(i, depth)| depth == 0 && s[i..].starts_with(separator))
+    {
+        result.push(&s[start..i]);
+        start = i + separator.len_utf8();
+    }
+    result.push(&s[start..]);
+    result
+}
+
+/// Iterate over the character positions of a string which are not part of a
+/// quoted identifier or string, with the parenthesis depth at each position.
+///
+/// The depth includes the parenthesis at the position itself, so a closing
+/// parenthesis is reported with the depth after it.
+fn top_level_positions(s: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut quote: Option<char> = None;
+    let mut depth = 0usize;
+    s.char_indices().filter_map(move |(i, c)| {
+        if let Some(q) = quote {
+            if c == q {
+                quote = None;
+            }
+            return None;
+        }
+        match c {
+            '\'' | '"' => {
+                quote = Some(c);
+                return None;
+            }
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+        Some((i, depth))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn strip_keyword() {
+        assert_eq!(
+            super::strip_keyword("CREATE INDEX x", "create"),
+            Some(" INDEX x")
+        );
+        assert_eq!(
+            super::strip_keyword("  create  index x", "CREATE INDEX"),
+            Some(" x")
+        );
+        assert_eq!(super::strip_keyword("CREATED", "CREATE"), None);
+        assert_eq!(super::strip_keyword("CREATE", "CREATE"), Some(""));
+    }
+
+    #[test]
+    fn identifier() {
+        assert_eq!(super::identifier(" pizza x"), Some(("pizza".into(), " x")));
+        assert_eq!(
+            super::identifier("\"My \"\"Table\"\"\".x"),
+            Some(("My \"Table\"".into(), ".x"))
+        );
+        assert_eq!(super::identifier("(x)"), None);
+        assert_eq!(super::identifier("\"unterminated"), None);
+    }
+
+    #[test]
+    fn quote_identifier() {
+        assert_eq!(super::quote_identifier("pizza"), "\"pizza\"");
+        assert_eq!(
+            super::quote_identifier("My \"Table\""),
+            "\"My \"\"Table\"\"\""
+        );
+    }
+
+    #[test]
+    fn format_identifier() {
+        assert_eq!(super::format_identifier("pizza_2"), "pizza_2");
+        assert_eq!(super::format_identifier("Pizza"), "\"Pizza\"");
+        assert_eq!(super::format_identifier("bob smith"), "\"bob smith\"");
+        assert_eq!(super::format_identifier("2pizza"), "\"2pizza\"");
+    }
+
+    #[test]
+    fn rename_roles() {
+        let mapping = [("alice", "Alice"), ("bob smith", "bob")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        assert_eq!(
+            super::rename_roles(
+                "GRANT SELECT ON TABLE public.alice TO alice,\"bob smith\" , carol;\n\
+                 REVOKE ALL ON TABLE public.pizza FROM alice;",
+                &["TO", "FROM"],
+                &mapping
+            ),
+            "GRANT SELECT ON TABLE public.alice TO \"Alice\",bob , carol;\n\
+             REVOKE ALL ON TABLE public.pizza FROM \"Alice\";"
+        );
+        assert_eq!(
+            super::rename_roles("SET x TO alice", &["OWNER TO"], &mapping),
+            "SET x TO alice"
+        );
+    }
+
+    #[test]
+    fn rename_acl_roles() {
+        let mapping = [("alice", "bob")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let rename = |stmt| super::rename_acl_roles(stmt, &mapping);
+        assert_eq!(
+            rename("\nGRANT SELECT ON TABLE public.t TO alice GRANTED BY alice"),
+            "\nGRANT SELECT ON TABLE public.t TO bob GRANTED BY bob"
+        );
+        assert_eq!(
+            rename("REVOKE ALL ON SCHEMA alice FROM alice"),
+            "REVOKE ALL ON SCHEMA alice FROM bob"
+        );
+        assert_eq!(
+            rename("ALTER DEFAULT PRIVILEGES FOR ROLE alice IN SCHEMA alice REVOKE ALL ON TABLES FROM alice"),
+            "ALTER DEFAULT PRIVILEGES FOR ROLE bob IN SCHEMA alice REVOKE ALL ON TABLES FROM bob"
+        );
+        assert_eq!(
+            rename("SET SESSION AUTHORIZATION alice"),
+            "SET SESSION AUTHORIZATION bob"
+        );
+        // TO and FROM outside of a GRANT or REVOKE are not role lists.
+        assert_eq!(
+            rename("SET search_path TO alice"),
+            "SET search_path TO alice"
+        );
+        assert_eq!(
+            rename("SELECT pg_catalog.lo_unlink(oid) FROM alice"),
+            "SELECT pg_catalog.lo_unlink(oid) FROM alice"
+        );
+    }
+
+    #[test]
+    fn string_literal() {
+        assert_eq!(
+            super::string_literal(" 'fast_ssd';"),
+            Some(("fast_ssd", ";"))
+        );
+        assert_eq!(super::string_literal("'it''s'"), Some(("it''s", "")));
+        assert_eq!(super::string_literal("fast_ssd"), None);
+        assert_eq!(super::string_literal("'unterminated"), None);
+    }
+
+    #[test]
+    fn qualified_name() {
+        assert_eq!(
+            super::qualified_name("public.\"Pizza\" USING"),
+            Some((vec!["public".into(), "Pizza".into()], " USING"))
+        );
+        assert_eq!(
+            super::qualified_name("pizza;"),
+            Some((vec!["pizza".into()], ";"))
+        );
+    }
+
+    #[test]
+    fn parenthesized() {
+        assert_eq!(
+            super::parenthesized(" (a, (b), ')') rest"),
+            Some(("a, (b), ')'", " rest"))
+        );
+        assert_eq!(super::parenthesized("(a"), None);
+    }
+
+    #[test]
+    fn find_keyword() {
+        let s = "ON t USING btree (\"where\") WHERE (x = 'WHERE')";
+        assert_eq!(super::find_keyword(s, "WHERE"), Some(27));
+        assert_eq!(super::find_keyword("nowhere", "WHERE"), None);
+    }
+
+    #[test]
+    fn statements() {
+        assert_eq!(
+            super::statements("SET x = ';';\nGRANT ALL ON \"a;b\" TO c;\n"),
+            vec!["SET x = ';'", "\nGRANT ALL ON \"a;b\" TO c"]
+        );
+        assert!(super::statements("  ").is_empty());
+    }
+
+    #[test]
+    fn set_statement() {
+        assert_eq!(
+            super::set_statement("SET client_encoding = 'UTF8';"),
+            Some(("client_encoding", "'UTF8'"))
+        );
+        assert_eq!(
+            super::set_statement("\nset search_path TO public, pg_catalog"),
+            Some(("search_path", "public, pg_catalog"))
+        );
+        assert_eq!(
+            super::set_statement("SET myapp.mode=strict"),
+            Some(("myapp.mode", "strict"))
+        );
+        assert_eq!(super::set_statement("SET row_security"), None);
+        assert_eq!(super::set_statement("SELECT 1"), None);
+    }
+
+    #[test]
+    fn split_list() {
+        assert_eq!(
+            super::split_list("SELECT(a, b), INSERT ,UPDATE"),
+            vec!["SELECT(a, b)", "INSERT", "UPDATE"]
+        );
+    }
+}
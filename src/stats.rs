@@ -0,0 +1,50 @@
+use crate::archive::Archive;
+use crate::types::Section;
+use std::collections::HashMap;
+
+/// Summary counts for the table of contents of an archive.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ArchiveStats {
+    /// Total number of TOC entries.
+    pub total: usize,
+    /// Number of entries for each entry type, such as `TABLE` or `INDEX`.
+    pub by_desc: HashMap<String, usize>,
+    /// Number of entries in each section.
+    pub by_section: HashMap<Section, usize>,
+}
+
+impl ArchiveStats {
+    /// Return the number of entries of the given type.
+    pub fn desc_count(&self, desc: &str) -> usize {
+        self.by_desc.get(desc).copied().unwrap_or(0)
+    }
+
+    /// Return the number of entries in the given section.
+    pub fn section_count(&self, section: Section) -> usize {
+        self.by_section.get(&section).copied().unwrap_or(0)
+    }
+}
+
+impl Archive {
+    /// Count the TOC entries by type and by section.
+    ///
+    /// ```rust
+    /// use std::fs::File;
+    /// use pgarchive::Archive;
+    ///
+    /// let archive = Archive::parse(&mut File::open("tests/test.pgdump").unwrap()).unwrap();
+    /// let stats = archive.stats();
+    /// println!("{} tables in {} entries", stats.desc_count("TABLE"), stats.total);
+    /// ```
+    pub fn stats(&self) -> ArchiveStats {
+        let mut stats = ArchiveStats {
+            total: self.toc_entries.len(),
+            ..Default::default()
+        };
+        for entry in &self.toc_entries {
+            *stats.by_desc.entry(entry.desc.clone()).or_default() += 1;
+            *stats.by_section.entry(entry.section).or_default() += 1;
+        }
+        stats
+    }
+}
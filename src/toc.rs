@@ -1,11 +1,80 @@
-use crate::archive::{K_VERS_1_10, K_VERS_1_11, K_VERS_1_14, K_VERS_1_16};
-use crate::io::ReadConfig;
-use crate::types::{ArchiveError, Offset, Oid, Section};
+use crate::archive::Archive;
+use crate::io::{CountingReader, ReadConfig};
+use crate::schema::AclGrant;
+use crate::types::{
+    ArchiveError, ArchiveFormat, DataAvailability, DataFormat, ObjectType, Offset, Oid, Section,
+    WarningKind,
+};
+use crate::versions::{K_VERS_1_10, K_VERS_1_11, K_VERS_1_14, K_VERS_1_16};
 use crate::Version;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
 use std::io::prelude::*;
 
+/// Identifier of a TOC entry.
+///
+/// pg_dump calls this a dump id. Valid ids are positive and fit in a signed
+/// 32 bit integer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct DumpId(pub(crate) u32);
+
+impl DumpId {
+    /// The largest dump id pg_dump can generate.
+    pub const MAX: DumpId = DumpId(i32::MAX as u32);
+
+    /// Create a dump id, or return `None` if `id` is not a valid dump id.
+    pub const fn new(id: u32) -> Option<DumpId> {
+        if id == 0 || id > DumpId::MAX.0 {
+            None
+        } else {
+            Some(DumpId(id))
+        }
+    }
+
+    /// Return the dump id as a number.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl TryFrom<i64> for DumpId {
+    type Error = ArchiveError;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        u32::try_from(value)
+            .ok()
+            .and_then(DumpId::new)
+            .ok_or(ArchiveError::InvalidDumpId(value))
+    }
+}
+
+impl From<DumpId> for u32 {
+    fn from(id: DumpId) -> u32 {
+        id.0
+    }
+}
+
+impl fmt::Display for DumpId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Maximum number of dependencies accepted for a single TOC entry.
+///
+/// pg_dump never writes more than a handful; this guards against corrupt
+/// archives with a dependency list that does not end.
+const MAX_DEPENDENCIES: usize = 65535;
+
 /// Type used for object identifiers
-pub type ID = i64;
+#[deprecated(note = "use DumpId instead")]
+pub type ID = DumpId;
 
 /// Object containing the data for a TOC entry.
 ///
@@ -14,10 +83,11 @@ pub type ID = i64;
 /// metadata, including the SQL statements to create and destroy database
 /// elements.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TocEntry {
-    pub id: ID,
+    pub id: DumpId,
     pub had_dumper: bool,
-    pub table_oid: u64,
+    pub table_oid: Oid,
     pub oid: Oid,
     /// Name of object that is created or modified.
     pub tag: String,
@@ -37,86 +107,204 @@ pub struct TocEntry {
     pub table_access_method: String,
     /// PostgreSQL user that owns the object.
     pub owner: String,
+    /// Whether the table was created `WITH OIDS`. This can only be set in
+    /// archives made before PostgreSQL 12, which removed support for it.
+    pub with_oids: bool,
     /// List of TOC entries that must be created first.
-    pub dependencies: Vec<ID>,
+    pub dependencies: Vec<DumpId>,
     /// File offset where data or blob content is stored.
     pub offset: Offset,
 }
 
 impl TocEntry {
+    /// Create a TOC entry without data.
+    ///
+    /// The OIDs are invalid and the other fields are empty. Use struct update
+    /// syntax to set them:
+    ///
+    /// ```rust
+    /// use pgarchive::{DumpId, Section, TocEntry};
+    ///
+    /// let entry = TocEntry {
+    ///     owner: String::from("wichert"),
+    ///     ..TocEntry::new(DumpId::new(1).unwrap(), Section::PreData, "SCHEMA", "menu")
+    /// };
+    /// ```
+    pub fn new(id: DumpId, section: Section, desc: &str, tag: &str) -> TocEntry {
+        TocEntry {
+            id,
+            had_dumper: false,
+            table_oid: Oid::INVALID,
+            oid: Oid::INVALID,
+            tag: String::from(tag),
+            desc: String::from(desc),
+            section,
+            defn: String::new(),
+            drop_stmt: String::new(),
+            copy_stmt: String::new(),
+            namespace: String::new(),
+            tablespace: String::new(),
+            table_access_method: String::new(),
+            owner: String::new(),
+            with_oids: false,
+            dependencies: Vec::new(),
+            offset: Offset::NoData,
+        }
+    }
+
     /// Read and parse a TOC entry from a file.
     ///
     /// This function is used by [`Archive::parse`](crate::archive::Archive::parse),
     /// and should not ne called directly.
-    pub fn parse(
-        f: &mut (impl Read + ?Sized),
+    pub fn parse<R: Read + ?Sized>(
+        f: &mut R,
         cfg: &ReadConfig,
         version: Version,
     ) -> Result<TocEntry, ArchiveError> {
+        Self::read(&mut CountingReader::new(f), cfg, version, &mut None).map(|(entry, _)| entry)
+    }
+
+    /// Read and parse a TOC entry, storing its id in `id` as soon as it is
+    /// known.
+    ///
+    /// Returns the entry and its relation kind.
+    fn read<R: Read>(
+        f: &mut CountingReader<R>,
+        cfg: &ReadConfig,
+        version: Version,
+        id: &mut Option<DumpId>,
+    ) -> Result<(TocEntry, i64), ArchiveError> {
         // Check `ReadToc` in `postgres/src/bin/pg_dump/pg_backup_archiver.c`
-        let id: ID = cfg.read_int(f)?;
-        if id < 0 {
-            return Err(ArchiveError::InvalidEntryData(id, "negative TOC id".into()));
-        }
+        f.start_field("TocEntry.id");
+        let entry_id = DumpId::try_from(cfg.read_int(f)?)?;
+        *id = Some(entry_id);
+        Self::parse_fields(f, cfg, version, entry_id).map_err(|e| match e {
+            ArchiveError::IOError(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                ArchiveError::TruncatedData {
+                    entry: format!("TOC entry {}", entry_id),
+                }
+            }
+            ArchiveError::InvalidData(message) => ArchiveError::InvalidEntryData(entry_id, message),
+            ArchiveError::InvalidOid(e) => ArchiveError::InvalidEntryData(entry_id, e.to_string()),
+            e => f.add_field(e),
+        })
+    }
+
+    /// Parse the TOC entry fields following the id.
+    ///
+    /// Generic format errors reported by the IO layer are turned into
+    /// [`ArchiveError::InvalidEntryData`] by [`TocEntry::read`].
+    fn parse_fields<R: Read>(
+        f: &mut CountingReader<R>,
+        cfg: &ReadConfig,
+        version: Version,
+        id: DumpId,
+    ) -> Result<(TocEntry, i64), ArchiveError> {
+        f.start_field("TocEntry.had_dumper");
         let had_dumper = cfg.read_int_bool(f)?;
+        f.start_field("TocEntry.table_oid");
         let table_oid = cfg.read_oid(f)?;
+        f.start_field("TocEntry.oid");
         let oid = cfg.read_oid(f)?;
+        f.start_field("TocEntry.tag");
         let tag = cfg.read_string(f)?;
+        f.start_field("TocEntry.desc");
         let desc = cfg.read_string(f)?;
+        f.start_field("TocEntry.section");
         let section: Section = if version >= K_VERS_1_11 {
-            cfg.read_int(f)?
+            let section = cfg.read_int(f)?;
+            section
                 .try_into()
-                .or(Err(ArchiveError::InvalidEntryData(
-                    id,
-                    "invalid section type".into(),
-                )))?
+                .or(Err(ArchiveError::InvalidSection(section)))?
         } else {
             Section::None
         };
+        f.start_field("TocEntry.defn");
         let defn = cfg.read_string(f)?;
+        f.start_field("TocEntry.drop_stmt");
         let drop_stmt = cfg.read_string(f)?;
+        f.start_field("TocEntry.copy_stmt");
         let copy_stmt = cfg.read_string(f)?;
+        f.start_field("TocEntry.namespace");
         let namespace = cfg.read_string(f)?;
 
+        f.start_field("TocEntry.tablespace");
         let tablespace = if version >= K_VERS_1_10 {
             cfg.read_string(f)?
         } else {
             String::new()
         };
 
+        f.start_field("TocEntry.table_access_method");
         let table_access_method = if version >= K_VERS_1_14 {
             cfg.read_string(f)?
         } else {
             String::new()
         };
 
-        let _relkind = if version >= K_VERS_1_16 {
+        f.start_field("TocEntry.relkind");
+        let relkind = if version >= K_VERS_1_16 {
             cfg.read_int(f)?
         } else {
             0
         };
 
+        f.start_field("TocEntry.owner");
         let owner = cfg.read_string(f)?;
-        if cfg.read_string_bool(f)? {
-            // This *must* be false
+        f.start_field("TocEntry.with_oids");
+        let with_oids = cfg.read_string_bool(f)?;
+        if with_oids && version >= K_VERS_1_14 {
+            // PostgreSQL 12 removed WITH OIDS, and its pg_dump always writes false.
             return Err(ArchiveError::InvalidEntryData(
                 id,
-                "mysterious value must be false".into(),
+                "WITH OIDS not supported for this archive version".into(),
             ));
         }
         let mut dependencies = Vec::new();
-        loop {
-            let dep_id = cfg.read_string(f)?;
-            if dep_id.is_empty() {
+        // The list of dependencies is terminated by a NULL string. Ignored
+        // dependencies count towards the limit as well.
+        for count in 0.. {
+            f.start_field("TocEntry.dependencies");
+            let Some(dep_id) = cfg.read_string_opt(f)? else {
                 break;
+            };
+            if count == MAX_DEPENDENCIES {
+                return Err(ArchiveError::InvalidEntryData(
+                    id,
+                    format!("more than {} dependencies", MAX_DEPENDENCIES),
+                ));
             }
-            dependencies.push(ID::from_str_radix(dep_id.as_str(), 10).or(Err(
-                ArchiveError::InvalidEntryData(id, "invalid dependency id".into()),
-            ))?);
+            let dep_id: i64 = dep_id.parse().or(Err(ArchiveError::InvalidEntryData(
+                id,
+                "invalid dependency id".into(),
+            )))?;
+            if dep_id == 0 {
+                f.warn(
+                    WarningKind::InvalidDependency,
+                    format!("TOC entry {} depends on id 0, which is ignored", id),
+                );
+                continue;
+            }
+            dependencies.push(DumpId::try_from(dep_id)?);
         }
-        let offset = cfg.read_offset(f)?;
+        let offset = match cfg.format {
+            ArchiveFormat::Custom => {
+                f.start_field("TocEntry.offset");
+                cfg.read_offset(f)?
+            }
+            // Tar archives store the name of the tar member holding the data
+            // instead of an offset.
+            ArchiveFormat::Tar => {
+                f.start_field("TocEntry.filename");
+                if cfg.read_string(f)?.is_empty() {
+                    Offset::NoData
+                } else {
+                    Offset::PosNotSet
+                }
+            }
+        };
 
-        Ok(TocEntry {
+        let entry = TocEntry {
             id,
             had_dumper,
             table_oid,
@@ -131,30 +319,218 @@ impl TocEntry {
             tablespace,
             table_access_method,
             owner,
+            with_oids,
             dependencies,
             offset,
-        })
+        };
+        Ok((entry, relkind))
+    }
+
+    /// Write the TOC entry in the custom archive format, with the data at
+    /// `offset`.
+    ///
+    /// This is the reverse of [`TocEntry::read`]. The reader does not
+    /// distinguish NULL from empty strings, so empty strings are written as
+    /// NULL, which is what pg_dump writes for fields which are not set. The
+    /// tablespace is the exception: pg_dump writes an empty tablespace for
+    /// objects in the default tablespace.
+    pub(crate) fn write<W: Write + ?Sized>(
+        &self,
+        f: &mut W,
+        cfg: &ReadConfig,
+        version: Version,
+        relkind: i64,
+        offset: Offset,
+    ) -> Result<(), ArchiveError> {
+        // Check `WriteToc` in `postgres/src/bin/pg_dump/pg_backup_archiver.c`
+        fn optional(value: &str) -> Option<&str> {
+            Some(value).filter(|value| !value.is_empty())
+        }
+        cfg.write_int(f, self.id.0.into())?;
+        cfg.write_int(f, self.had_dumper.into())?;
+        cfg.write_string(f, &self.table_oid.to_string())?;
+        cfg.write_string(f, &self.oid.to_string())?;
+        cfg.write_string(f, &self.tag)?;
+        cfg.write_string(f, &self.desc)?;
+        if version >= K_VERS_1_11 {
+            cfg.write_int(f, self.section as i64)?;
+        }
+        cfg.write_string_opt(f, optional(&self.defn))?;
+        cfg.write_string_opt(f, optional(&self.drop_stmt))?;
+        cfg.write_string_opt(f, optional(&self.copy_stmt))?;
+        cfg.write_string_opt(f, optional(&self.namespace))?;
+        if version >= K_VERS_1_10 {
+            cfg.write_string(f, &self.tablespace)?;
+        }
+        if version >= K_VERS_1_14 {
+            cfg.write_string_opt(f, optional(&self.table_access_method))?;
+        }
+        if version >= K_VERS_1_16 {
+            cfg.write_int(f, relkind)?;
+        }
+        cfg.write_string_opt(f, optional(&self.owner))?;
+        cfg.write_string(f, if self.with_oids { "true" } else { "false" })?;
+        for dependency in &self.dependencies {
+            cfg.write_string(f, &dependency.to_string())?;
+        }
+        cfg.write_string_opt(f, None)?;
+        cfg.write_offset(f, offset)
+    }
+
+    /// Return the name of the object, including its schema if it has one.
+    pub(crate) fn qualified_name(&self) -> String {
+        if self.namespace.is_empty() {
+            self.tag.clone()
+        } else {
+            format!("{}.{}", self.namespace, self.tag)
+        }
+    }
+
+    /// Return a copy of this entry with a different id.
+    pub fn clone_with_new_id(&self, new_id: DumpId) -> TocEntry {
+        TocEntry {
+            id: new_id,
+            ..self.clone()
+        }
+    }
+
+    /// Return the format used to store the data for this entry.
+    ///
+    /// This is determined from the TOC entry only, so no data needs to be read.
+    pub fn data_format(&self) -> DataFormat {
+        if !self.had_dumper {
+            return DataFormat::None;
+        }
+        if !self.copy_stmt.is_empty() {
+            return DataFormat::Copy;
+        }
+        match ObjectType::known(&self.desc) {
+            // Data created with pg_dump --inserts has no COPY statement.
+            Some(ObjectType::TableData) => DataFormat::Inserts,
+            Some(ObjectType::Blobs) => DataFormat::Blobs,
+            _ => DataFormat::Unknown,
+        }
+    }
+
+    /// Return whether the data for this entry can be located.
+    ///
+    /// This is determined from the [offset](TocEntry::offset) only, so no
+    /// data needs to be read. Tar archives do not record the position of the
+    /// data in the table of contents, so their entries with data report
+    /// [`DataAvailability::PositionUnknown`]. Use
+    /// [`Archive::data_availability`] to also handle tar archives.
+    pub fn data_availability(&self) -> DataAvailability {
+        match self.offset {
+            Offset::PosSet(_) => DataAvailability::Present,
+            Offset::NoData => DataAvailability::NoData,
+            Offset::Unknown | Offset::PosNotSet => DataAvailability::PositionUnknown,
+        }
+    }
+
+    /// Return true if this entry is in [`Section::None`] and has no
+    /// namespace.
+    ///
+    /// This is a check of the TOC entry only. The `DATABASE` entry is in
+    /// [`Section::PreData`], so it is not included. `ACL` and `COMMENT`
+    /// entries for objects which are not in a schema, such as schemas and
+    /// extensions, are included.
+    pub fn is_global(&self) -> bool {
+        self.section == Section::None && self.namespace.is_empty()
+    }
+
+    /// Return the type of object described by this entry.
+    ///
+    /// This is [`desc`](TocEntry::desc) parsed as an [`ObjectType`].
+    pub fn object_type(&self) -> ObjectType {
+        ObjectType::from(self.desc.as_str())
+    }
+
+    /// Return true if this entry describes an object of type `object_type`.
+    ///
+    /// This compares [`desc`](TocEntry::desc) directly, so unlike comparing
+    /// the result of [`object_type`](TocEntry::object_type) it does not
+    /// allocate for unknown object types.
+    pub fn is(&self, object_type: ObjectType) -> bool {
+        self.desc == object_type.as_str()
+    }
+
+    /// Return the privileges granted by an `ACL` entry.
+    ///
+    /// For all other entries an empty list is returned.
+    pub fn acl_grants(&self) -> Vec<AclGrant> {
+        if !self.is(ObjectType::Acl) {
+            return Vec::new();
+        }
+        AclGrant::parse_all(&self.defn)
+    }
+
+    /// Return the owner of the object.
+    ///
+    /// Some entries, such as those for global objects, do not have an owner.
+    /// For those the owner of the `DATABASE` entry in the archive is returned
+    /// instead. If that is not available either an empty string is returned.
+    pub fn effective_owner<'a>(&'a self, archive: &'a Archive) -> &'a str {
+        if !self.owner.is_empty() {
+            return &self.owner;
+        }
+        archive
+            .toc_entries
+            .iter()
+            .find(|e| e.is(ObjectType::Database))
+            .map_or("", |e| e.owner.as_str())
     }
 }
 
-pub fn read_toc(
-    f: &mut (impl Read + ?Sized),
+/// The table of contents as read from an archive.
+#[derive(Debug, Default)]
+pub struct Toc {
+    pub entries: Vec<TocEntry>,
+    /// The relation kind of entries for relations, which pg_dump stores
+    /// since archive format 1.16. Entries without one are left out.
+    pub relkinds: HashMap<DumpId, i64>,
+}
+
+/// Read the table of contents, stopping after at most `max_entries` entries.
+///
+/// Errors for an entry are wrapped in an [`ArchiveError::ParseError`]
+/// describing the entry and the field that could not be read.
+pub fn read_toc<R: Read>(
+    f: &mut CountingReader<R>,
     cfg: &ReadConfig,
     version: Version,
-) -> Result<Vec<TocEntry>, ArchiveError> {
-    let num_entries = cfg.read_int(f)?;
-    let mut entries = Vec::with_capacity(num_entries as usize);
+    max_entries: usize,
+) -> Result<Toc, ArchiveError> {
+    f.start_field("Archive.toc_entries");
+    let num_entries = (cfg.read_int(f)?.max(0) as usize).min(max_entries);
+    // The count can not be trusted, so do not reserve room for all entries
+    // up front.
+    let mut entries = Vec::with_capacity(num_entries.min(1024));
+    let mut relkinds = HashMap::new();
 
-    for _ in 0..num_entries {
-        entries.push(TocEntry::parse(f, cfg, version)?);
+    for index in 0..num_entries {
+        let mut id = None;
+        let (entry, relkind) = TocEntry::read(f, cfg, version, &mut id).map_err(|e| {
+            let context = match id {
+                Some(id) => format!("{} of TOC entry {} (id {})", f.field(), index, id),
+                None => format!("{} of TOC entry {}", f.field(), index),
+            };
+            f.wrap_error(e, context)
+        })?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(id = %entry.id, tag = %entry.tag, desc = %entry.desc, "read TOC entry");
+        if relkind != 0 {
+            relkinds.insert(entry.id, relkind);
+        }
+        entries.push(entry);
     }
-    Ok(entries)
+    Ok(Toc { entries, relkinds })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::archive::K_VERS_1_15;
+    use crate::schema::ExtensionInfo;
+    use crate::versions::K_VERS_1_15;
     use hex_literal::hex;
 
     #[test]
@@ -174,7 +550,7 @@ mod tests {
             "01 01 00 00 00" // Tablespace
             "01 01 00 00 00" // TableAccessMethod
             "01 01 00 00 00" // Owner
-            "00 05 00 00 00 66 61 6c 73 65" // mandatory false
+            "00 05 00 00 00 66 61 6c 73 65" // with oids
             "01 01 00 00 00" // end of dependencies
             "03" // offset flag
             "00 00 00 00 00 00 00 00" // offset
@@ -183,16 +559,17 @@ mod tests {
         let cfg = ReadConfig {
             int_size: 4,
             offset_size: 8,
+            ..ReadConfig::new()
         };
 
         let entry = TocEntry::parse(&mut input, &cfg, K_VERS_1_15)?;
         assert_eq!(
             entry,
             TocEntry {
-                id: 0x118e,
+                id: DumpId(0x118e),
                 had_dumper: false,
-                table_oid: 0,
-                oid: 0,
+                table_oid: Oid::INVALID,
+                oid: Oid::INVALID,
                 tag: String::from("ENCODING"),
                 desc: String::from("ENCODING"),
                 section: Section::PreData,
@@ -203,6 +580,7 @@ mod tests {
                 tablespace: String::from(""),
                 table_access_method: String::from(""),
                 owner: String::from(""),
+                with_oids: false,
                 dependencies: vec![],
                 offset: Offset::NoData,
             }
@@ -227,7 +605,7 @@ mod tests {
                 "01 01 00 00 00" // Tablespace
                 "01 01 00 00 00" // TableAccessMethod
                 "01 01 00 00 00" // Owner
-                "00 05 00 00 00 66 61 6c 73 65" // mandatory false
+                "00 05 00 00 00 66 61 6c 73 65" // with oids
                 "01 01 00 00 00" // end of dependencies
                 "03" // offset flag
                 "00 00 00 00 00 00 00 00" // offset
@@ -236,16 +614,17 @@ mod tests {
         let cfg = ReadConfig {
             int_size: 4,
             offset_size: 8,
+            ..ReadConfig::new()
         };
 
         let entry = TocEntry::parse(&mut input, &cfg, K_VERS_1_15)?;
         assert_eq!(
             entry,
             TocEntry {
-                id: 2,
+                id: DumpId(2),
                 had_dumper: false,
-                table_oid: 3079,
-                oid: 33708,
+                table_oid: Oid(3079),
+                oid: Oid(33708),
                 tag: String::from("postgis"),
                 desc: String::from("EXTENSION"),
                 section: Section::PreData,
@@ -258,10 +637,21 @@ mod tests {
                 tablespace: String::from(""),
                 table_access_method: String::from(""),
                 owner: String::from(""),
+                with_oids: false,
                 dependencies: vec![],
                 offset: Offset::NoData,
             }
         );
+
+        let archive = Archive::builder().toc_entry(entry).build();
+        assert_eq!(
+            archive.extensions(),
+            vec![ExtensionInfo {
+                name: String::from("postgis"),
+                schema: String::from("public"),
+                version: None,
+            }]
+        );
         Ok(())
     }
 
@@ -282,7 +672,7 @@ mod tests {
                     "01 01 00 00 00" // Tablespace
                     "01 01 00 00 00" // TableAccessMethod
                     "00 07 00 00 00 77 69 63 68 65 72 74" // Owner
-                    "00 05 00 00 00 66 61 6c 73 65" // mandatory false
+                    "00 05 00 00 00 66 61 6c 73 65" // with oids
                     "00 03 00 00 00 32 31 33" // Dependency 1
                     "01 01 00 00 00" // end of dependencies
                     "02" // offset flag
@@ -292,16 +682,17 @@ mod tests {
         let cfg = ReadConfig {
             int_size: 4,
             offset_size: 8,
+            ..ReadConfig::new()
         };
 
         let entry = TocEntry::parse(&mut input, &cfg, K_VERS_1_15)?;
         assert_eq!(
             entry,
             TocEntry {
-                id: 0x118a,
+                id: DumpId(0x118a),
                 had_dumper: true,
-                table_oid: 1,
-                oid: 33686,
+                table_oid: Oid(1),
+                oid: Oid(33686),
                 tag: String::from("pizza"),
                 desc: String::from("TABLE DATA"),
                 section: Section::Data,
@@ -312,23 +703,116 @@ mod tests {
                 tablespace: String::from(""),
                 table_access_method: String::from(""),
                 owner: String::from("wichert"),
-                dependencies: vec![213],
+                with_oids: false,
+                dependencies: vec![DumpId(213)],
                 offset: Offset::PosSet(0x16d7),
             }
         );
         Ok(())
     }
 
+    #[test]
+    fn with_oids_toc_entry() -> Result<(), ArchiveError> {
+        let input = hex!(
+            "00 07 00 00 00" // ID
+            "00 00 00 00 00" // HadDumper
+            "00 01 00 00 00 30" // Table OID
+            "00 05 00 00 00 31 36 33 38 34" // OID
+            "00 05 00 00 00 70 69 7a 7a 61" // Tag
+            "00 05 00 00 00 54 41 42 4c 45" // Desc
+            "00 02 00 00 00" // Section
+            "01 01 00 00 00" // Defn
+            "01 01 00 00 00" // DropStmt
+            "01 01 00 00 00" // CopyStmt
+            "00 06 00 00 00 70 75 62 6c 69 63" // Namespace
+            "01 01 00 00 00" // Tablespace
+            "00 07 00 00 00 77 69 63 68 65 72 74" // Owner
+            "00 04 00 00 00 74 72 75 65" // with oids
+            "01 01 00 00 00" // end of dependencies
+            "03" // offset flag
+            "00 00 00 00 00 00 00 00" // offset
+        );
+
+        let cfg = ReadConfig {
+            int_size: 4,
+            offset_size: 8,
+            ..ReadConfig::new()
+        };
+
+        let entry = TocEntry::parse(&mut &input[..], &cfg, K_VERS_1_11)?;
+        assert_eq!(entry.tag, "pizza");
+        assert!(entry.with_oids);
+        assert_eq!(entry.owner, "wichert");
+        assert_eq!(entry.offset, Offset::NoData);
+
+        // PostgreSQL 12 removed WITH OIDS. The 1.14 layout adds a table
+        // access method.
+        let mut input = input.to_vec();
+        input.splice(72..72, hex!("01 01 00 00 00"));
+        assert!(matches!(
+            TocEntry::parse(&mut &input[..], &cfg, K_VERS_1_14),
+            Err(ArchiveError::InvalidEntryData(DumpId(7), _))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_dependencies() {
+        let header = hex!(
+            "00 07 00 00 00" // ID
+            "00 00 00 00 00" // HadDumper
+            "00 01 00 00 00 30" // Table OID
+            "00 05 00 00 00 31 36 33 38 34" // OID
+            "00 05 00 00 00 70 69 7a 7a 61" // Tag
+            "00 05 00 00 00 54 41 42 4c 45" // Desc
+            "00 02 00 00 00" // Section
+            "01 01 00 00 00" // Defn
+            "01 01 00 00 00" // DropStmt
+            "01 01 00 00 00" // CopyStmt
+            "00 06 00 00 00 70 75 62 6c 69 63" // Namespace
+            "01 01 00 00 00" // Tablespace
+            "00 07 00 00 00 77 69 63 68 65 72 74" // Owner
+            "00 05 00 00 00 66 61 6c 73 65" // with oids
+        );
+        let cfg = ReadConfig {
+            int_size: 4,
+            offset_size: 8,
+            ..ReadConfig::new()
+        };
+        // Dependencies on id 0 are ignored, but still count.
+        for dependency in [hex!("00 01 00 00 00 31"), hex!("00 01 00 00 00 30")] {
+            let mut input = header.to_vec();
+            for _ in 0..=MAX_DEPENDENCIES {
+                input.extend(dependency);
+            }
+            input.extend(hex!(
+                "01 01 00 00 00" // end of dependencies
+                "03" // offset flag
+                "00 00 00 00 00 00 00 00" // offset
+            ));
+            assert!(matches!(
+                TocEntry::parse(&mut &input[..], &cfg, K_VERS_1_11),
+                Err(ArchiveError::InvalidEntryData(DumpId(7), _))
+            ));
+        }
+    }
+
     #[test]
     fn empty_toc() -> Result<(), ArchiveError> {
         let mut input = &hex!("00 00 00 00 00")[..];
         let cfg = ReadConfig {
             int_size: 4,
             offset_size: 8,
+            ..ReadConfig::new()
         };
 
-        let toc = read_toc(&mut input, &cfg, K_VERS_1_15)?;
-        assert!(toc.is_empty());
+        let toc = read_toc(
+            &mut CountingReader::new(&mut input),
+            &cfg,
+            K_VERS_1_15,
+            usize::MAX,
+        )?;
+        assert!(toc.entries.is_empty());
         Ok(())
     }
 
@@ -352,7 +836,7 @@ mod tests {
             "01 01 00 00 00" // Tablespace
             "01 01 00 00 00" // TableAccessMethod
             "01 01 00 00 00" // Owner
-            "00 05 00 00 00 66 61 6c 73 65" // mandatory false
+            "00 05 00 00 00 66 61 6c 73 65" // with oids
             "01 01 00 00 00" // end of dependencies
             "03" // offset flag
             "00 00 00 00 00 00 00 00" // offset
@@ -360,10 +844,332 @@ mod tests {
         let cfg = ReadConfig {
             int_size: 4,
             offset_size: 8,
+            ..ReadConfig::new()
         };
 
-        let toc = read_toc(&mut input, &cfg, K_VERS_1_15)?;
-        assert_eq!(toc.len(), 1);
+        let toc = read_toc(
+            &mut CountingReader::new(&mut input),
+            &cfg,
+            K_VERS_1_15,
+            usize::MAX,
+        )?;
+        assert_eq!(toc.entries.len(), 1);
         Ok(())
     }
+
+    #[test]
+    fn limited_toc() -> Result<(), ArchiveError> {
+        let mut input = &hex!(
+            // number of entries
+            "00 02 00 00 00"
+            // Entry 1
+            "00 8e 11 00 00" // ID
+            "00 00 00 00 00" // had dumper
+            "00 01 00 00 00 30" // Table OID
+            "00 01 00 00 00 30" // OID
+            "00 08 00 00 00 45 4e 43 4f 44 49 4e 47" // Tag
+            "00 08 00 00 00 45 4e 43 4f 44 49 4e 47" // Desc
+            "00 02 00 00 00" // Section
+            "00 1e 00 00 00 53 45 54 20 63 6c 69 65 6e 74 5f 65 6e 63 6f 64 69 6e 67 20 3d 20 27 55 54 46 38 27 3b 0a" // Defn
+            "01 01 00 00 00" // DropStmt
+            "01 01 00 00 00" // CopyStmt
+            "01 01 00 00 00" // Namespace
+            "01 01 00 00 00" // Tablespace
+            "01 01 00 00 00" // TableAccessMethod
+            "01 01 00 00 00" // Owner
+            "00 05 00 00 00 66 61 6c 73 65" // with oids
+            "01 01 00 00 00" // end of dependencies
+            "03" // offset flag
+            "00 00 00 00 00 00 00 00" // offset
+            // Entry 2 is corrupt
+            "ff ff ff ff ff"
+        )[..];
+        let cfg = ReadConfig {
+            int_size: 4,
+            offset_size: 8,
+            ..ReadConfig::new()
+        };
+
+        let toc = read_toc(&mut CountingReader::new(&mut input), &cfg, K_VERS_1_15, 1)?;
+        assert_eq!(toc.entries.len(), 1);
+        assert_eq!(toc.entries[0].tag, "ENCODING");
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_toc_entry() {
+        let cfg = ReadConfig {
+            int_size: 4,
+            offset_size: 8,
+            ..ReadConfig::new()
+        };
+
+        // truncated after the id
+        let mut input = &hex!("00 2a 00 00 00" "00 00")[..];
+        assert!(matches!(
+            TocEntry::parse(&mut input, &cfg, K_VERS_1_15),
+            Err(ArchiveError::TruncatedData { entry }) if entry == "TOC entry 42"
+        ));
+
+        // invalid table OID
+        let mut input = &hex!(
+            "00 2a 00 00 00" // ID
+            "00 00 00 00 00" // had dumper
+            "00 01 00 00 00 78" // Table OID
+        )[..];
+        assert!(matches!(
+            TocEntry::parse(&mut input, &cfg, K_VERS_1_15),
+            Err(ArchiveError::InvalidEntryData(DumpId(42), _))
+        ));
+    }
+
+    #[test]
+    fn invalid_dump_id() {
+        let cfg = ReadConfig {
+            int_size: 4,
+            offset_size: 8,
+            ..ReadConfig::new()
+        };
+
+        // negative id
+        let mut input = &hex!("01 2a 00 00 00")[..];
+        assert!(matches!(
+            TocEntry::parse(&mut input, &cfg, K_VERS_1_15),
+            Err(ArchiveError::InvalidDumpId(-42))
+        ));
+
+        // zero is not a valid id
+        let mut input = &hex!("00 00 00 00 00")[..];
+        assert!(matches!(
+            TocEntry::parse(&mut input, &cfg, K_VERS_1_15),
+            Err(ArchiveError::InvalidDumpId(0))
+        ));
+
+        // larger than a signed 32 bit integer
+        let mut input = &hex!("00 00 00 00 80")[..];
+        assert!(matches!(
+            TocEntry::parse(&mut input, &cfg, K_VERS_1_15),
+            Err(ArchiveError::InvalidDumpId(0x8000_0000))
+        ));
+
+        // out of range dependency
+        let mut input = &hex!(
+            "00 2a 00 00 00" // ID
+            "00 00 00 00 00" // had dumper
+            "00 01 00 00 00 30" // Table OID
+            "00 01 00 00 00 30" // OID
+            "00 08 00 00 00 45 4e 43 4f 44 49 4e 47" // Tag
+            "00 08 00 00 00 45 4e 43 4f 44 49 4e 47" // Desc
+            "00 02 00 00 00" // Section
+            "01 01 00 00 00" // Defn
+            "01 01 00 00 00" // DropStmt
+            "01 01 00 00 00" // CopyStmt
+            "01 01 00 00 00" // Namespace
+            "01 01 00 00 00" // Tablespace
+            "01 01 00 00 00" // TableAccessMethod
+            "01 01 00 00 00" // Owner
+            "00 05 00 00 00 66 61 6c 73 65" // with oids
+            "00 0d 00 00 00 31 30 39 39 35 31 31 36 32 37 37 37 36" // dependency 1099511627776
+            "01 01 00 00 00" // end of dependencies
+            "03" // offset flag
+            "00 00 00 00 00 00 00 00" // offset
+        )[..];
+        assert!(matches!(
+            TocEntry::parse(&mut input, &cfg, K_VERS_1_15),
+            Err(ArchiveError::InvalidDumpId(1_099_511_627_776))
+        ));
+    }
+
+    #[test]
+    fn dump_id_conversion() {
+        assert_eq!(DumpId::try_from(1).unwrap(), DumpId(1));
+        assert_eq!(DumpId::try_from(i32::MAX as i64).unwrap(), DumpId::MAX);
+        assert!(DumpId::try_from(-1).is_err());
+        assert_eq!(u32::from(DumpId(42)), 42);
+        assert_eq!(DumpId(42).to_string(), "42");
+        assert_eq!(DumpId::new(42), Some(DumpId(42)));
+        assert_eq!(DumpId::new(42).map(DumpId::get), Some(42));
+        assert_eq!(DumpId::new(0), None);
+        assert_eq!(DumpId::new(i32::MAX as u32 + 1), None);
+        assert!(DumpId::try_from(i32::MAX as i64 + 1).is_err());
+    }
+
+    fn pizza_table_data() -> TocEntry {
+        let mut input = &hex!(
+                    "00 8a 11 00 00" // ID
+                    "00 01 00 00 00" // HadDumper
+                    "00 01 00 00 00 31" // Table OID
+                    "00 05 00 00 00 33 33 36 38 36" // OID
+                    "00 05 00 00 00 70 69 7a 7a 61" // Tag
+                    "00 0a 00 00 00 54 41 42 4c 45 20 44 41 54 41" // Desc
+                    "00 03 00 00 00" // Section
+                    "01 01 00 00 00" // Defn
+                    "01 01 00 00 00" // DropStmt
+                    "00 2f 00 00 00 43 4f 50 59 20 70 75 62 6c 69 63 2e 70 69 7a 7a 61 20 28 70 69 7a 7a 61 5f 69 64 2c 20 6e 61 6d 65 29 20 46 52 4f 4d 20 73 74 64 69 6e 3b 0a" // CopyStmt
+                    "00 06 00 00 00 70 75 62 6c 69 63" // Namespace
+                    "01 01 00 00 00" // Tablespace
+                    "01 01 00 00 00" // TableAccessMethod
+                    "00 07 00 00 00 77 69 63 68 65 72 74" // Owner
+                    "00 05 00 00 00 66 61 6c 73 65" // with oids
+                    "01 01 00 00 00" // end of dependencies
+                    "02" // offset flag
+                    "d7 16 00 00 00 00 00 00" // offset
+        )[..];
+        let cfg = ReadConfig {
+            int_size: 4,
+            offset_size: 8,
+            ..ReadConfig::new()
+        };
+        TocEntry::parse(&mut input, &cfg, K_VERS_1_15).unwrap()
+    }
+
+    #[test]
+    fn write_entry() -> Result<(), ArchiveError> {
+        let cfg = ReadConfig {
+            int_size: 4,
+            offset_size: 8,
+            ..ReadConfig::new()
+        };
+        let entry = TocEntry {
+            dependencies: vec![DumpId(7), DumpId(9)],
+            ..pizza_table_data()
+        };
+        for version in [K_VERS_1_14, K_VERS_1_15, K_VERS_1_16] {
+            let mut output = Vec::new();
+            entry.write(&mut output, &cfg, version, 114, entry.offset)?;
+            let (written, relkind) = TocEntry::read(
+                &mut CountingReader::new(&output[..]),
+                &cfg,
+                version,
+                &mut None,
+            )?;
+            assert_eq!(written, entry);
+            assert_eq!(relkind, if version >= K_VERS_1_16 { 114 } else { 0 });
+        }
+
+        // Empty strings are written as NULL, except for the tablespace.
+        let mut output = Vec::new();
+        let entry = pizza_table_data();
+        entry.write(&mut output, &cfg, K_VERS_1_15, 0, Offset::NoData)?;
+        assert_eq!(
+            output,
+            hex!(
+                "00 8a 11 00 00" // ID
+                "00 01 00 00 00" // HadDumper
+                "00 01 00 00 00 31" // Table OID
+                "00 05 00 00 00 33 33 36 38 36" // OID
+                "00 05 00 00 00 70 69 7a 7a 61" // Tag
+                "00 0a 00 00 00 54 41 42 4c 45 20 44 41 54 41" // Desc
+                "00 03 00 00 00" // Section
+                "01 01 00 00 00" // Defn
+                "01 01 00 00 00" // DropStmt
+                "00 2f 00 00 00 43 4f 50 59 20 70 75 62 6c 69 63 2e 70 69 7a 7a 61 20 28 70 69 7a 7a 61 5f 69 64 2c 20 6e 61 6d 65 29 20 46 52 4f 4d 20 73 74 64 69 6e 3b 0a" // CopyStmt
+                "00 06 00 00 00 70 75 62 6c 69 63" // Namespace
+                "00 00 00 00 00" // Tablespace
+                "01 01 00 00 00" // TableAccessMethod
+                "00 07 00 00 00 77 69 63 68 65 72 74" // Owner
+                "00 05 00 00 00 66 61 6c 73 65" // with oids
+                "01 01 00 00 00" // end of dependencies
+                "03" // offset flag
+                "00 00 00 00 00 00 00 00" // offset
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn data_format() {
+        let entry = pizza_table_data();
+        assert_eq!(entry.data_format(), DataFormat::Copy);
+
+        // Data created with pg_dump --inserts has no COPY statement
+        let inserts = TocEntry {
+            copy_stmt: String::new(),
+            ..entry.clone()
+        };
+        assert_eq!(inserts.data_format(), DataFormat::Inserts);
+
+        let blobs = TocEntry {
+            desc: String::from("BLOBS"),
+            tag: String::from("BLOBS"),
+            copy_stmt: String::new(),
+            ..entry.clone()
+        };
+        assert_eq!(blobs.data_format(), DataFormat::Blobs);
+
+        let unknown = TocEntry {
+            desc: String::from("EXTENSION DATA"),
+            copy_stmt: String::new(),
+            ..entry.clone()
+        };
+        assert_eq!(unknown.data_format(), DataFormat::Unknown);
+
+        let no_data = TocEntry {
+            had_dumper: false,
+            ..entry
+        };
+        assert_eq!(no_data.data_format(), DataFormat::None);
+    }
+
+    #[test]
+    fn is() {
+        let entry = pizza_table_data();
+        assert!(entry.is(ObjectType::TableData));
+        assert!(!entry.is(ObjectType::Table));
+        let other = TocEntry {
+            desc: String::from("OPERATOR CLASS"),
+            ..entry
+        };
+        assert!(other.is(ObjectType::Other("OPERATOR CLASS".into())));
+    }
+
+    #[test]
+    fn data_availability() {
+        let entry = pizza_table_data();
+        assert_eq!(entry.data_availability(), DataAvailability::Present);
+        for (offset, expected) in [
+            (Offset::NoData, DataAvailability::NoData),
+            (Offset::PosNotSet, DataAvailability::PositionUnknown),
+            (Offset::Unknown, DataAvailability::PositionUnknown),
+        ] {
+            let entry = TocEntry {
+                offset,
+                ..entry.clone()
+            };
+            assert_eq!(entry.data_availability(), expected);
+        }
+    }
+
+    #[test]
+    fn is_global() {
+        let entry = pizza_table_data();
+        assert!(!entry.is_global());
+
+        let global = TocEntry {
+            section: Section::None,
+            namespace: String::new(),
+            ..entry.clone()
+        };
+        assert!(global.is_global());
+
+        let comment = TocEntry {
+            section: Section::None,
+            ..entry
+        };
+        assert!(!comment.is_global());
+    }
+
+    #[test]
+    fn clone_with_new_id() {
+        let entry = pizza_table_data();
+        let copy = entry.clone_with_new_id(DumpId(42));
+        assert_eq!(copy.id, DumpId(42));
+        assert_eq!(
+            TocEntry {
+                id: entry.id,
+                ..copy
+            },
+            entry
+        );
+    }
 }
@@ -1,54 +1,455 @@
+use crate::toc::TocEntry;
 use std::fmt;
 use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::string::FromUtf8Error;
 use thiserror::Error;
 
-/// Type used for PostgreSQL version numbers
-pub type Version = (u8, u8, u8);
+/// Archive format version.
+///
+/// Versions can be compared with each other, with `(major, minor, patch)`
+/// tuples and with version strings:
+///
+/// ```rust
+/// # use pgarchive::Version;
+/// let version = Version::new(1, 15, 0);
+/// assert!(version > (1, 14, 0));
+/// assert!(version >= "1.14");
+/// assert_eq!(version.to_string(), "1.15.0");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+}
+
+impl Version {
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl From<(u8, u8, u8)> for Version {
+    fn from((major, minor, patch): (u8, u8, u8)) -> Self {
+        Version::new(major, minor, patch)
+    }
+}
+
+impl From<Version> for (u8, u8, u8) {
+    fn from(v: Version) -> Self {
+        (v.major, v.minor, v.patch)
+    }
+}
+
+impl PartialEq<(u8, u8, u8)> for Version {
+    fn eq(&self, other: &(u8, u8, u8)) -> bool {
+        *self == Version::from(*other)
+    }
+}
+
+impl PartialOrd<(u8, u8, u8)> for Version {
+    fn partial_cmp(&self, other: &(u8, u8, u8)) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(&Version::from(*other)))
+    }
+}
+
+// Comparing with a string that is not a valid version is always false.
+impl PartialEq<&str> for Version {
+    fn eq(&self, other: &&str) -> bool {
+        other.parse::<Version>().is_ok_and(|v| *self == v)
+    }
+}
+
+impl PartialOrd<&str> for Version {
+    fn partial_cmp(&self, other: &&str) -> Option<std::cmp::Ordering> {
+        other.parse::<Version>().ok().map(|v| self.cmp(&v))
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Error returned when parsing an invalid version.
+#[derive(Error, Debug, PartialEq)]
+#[error("invalid version \"{0}\", expected major.minor or major.minor.patch")]
+pub struct ParseVersionError(String);
+
+impl FromStr for Version {
+    type Err = ParseVersionError;
+
+    /// Parse a version in the form `1.16` or `1.16.0`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<u8> = s
+            .split('.')
+            .map(|p| p.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| ParseVersionError(s.into()))?;
+        match parts[..] {
+            [major, minor] => Ok(Version::new(major, minor, 0)),
+            [major, minor, patch] => Ok(Version::new(major, minor, patch)),
+            _ => Err(ParseVersionError(s.into())),
+        }
+    }
+}
 
 /// Error type used for archive processing errors.
 ///
 /// Errors can be caused by underlying IO errors, unsupported features or
 /// invalid data.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum ArchiveError {
     /// An IO errors occured while reading data.
     #[error("IO error reading data")]
-    IOError(#[from] io::Error),
+    IOError(#[source] io::Error),
+    /// An archive file could not be opened by [`Archive::open`](crate::Archive::open).
+    #[error("can not open {}", path.display())]
+    OpenError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
     /// Invalid data was found. This should only happen if the archive is
     /// corrupted (or pgarchive has a bug).
+    ///
+    /// This is only used for problems that are not covered by a more specific
+    /// variant.
     #[error("format error: {0}")]
     InvalidData(String),
+    /// The file does not start with the `PGDMP` magic bytes, so it is not a
+    /// custom format archive.
+    #[error("file does not start with PGDMP")]
+    BadMagic,
+    /// The compression method in the archive header is not known.
+    #[deprecated(note = "unknown compression methods are read as `CompressionMethod::Unknown`")]
+    #[error("invalid compression method {0}")]
+    InvalidCompression(u8),
+    /// The creation date in the header is not a valid date.
+    ///
+    /// This includes the raw `struct tm` values from the header: months count
+    /// from 0, and years from 1900.
+    #[error("invalid {field} in creation date (sec={sec} min={min} hour={hour} mday={mday} mon={mon} year={year})")]
+    InvalidCreateDate {
+        /// Name of the first invalid component, for example `month`.
+        field: &'static str,
+        sec: i64,
+        min: i64,
+        hour: i64,
+        mday: i64,
+        mon: i64,
+        year: i64,
+    },
+    /// A TOC entry has an unknown section.
+    #[error("invalid section type {0}")]
+    InvalidSection(i64),
+    /// An OID is not a number, or does not fit in 32 bits.
+    #[error(transparent)]
+    InvalidOid(#[from] ParseOidError),
+    /// An offset has an unknown flag.
+    #[error("invalid offset flag {0}")]
+    InvalidOffsetFlag(u8),
+    /// A string is not valid UTF-8.
+    ///
+    /// `field` is the name of the field containing the string, if it is
+    /// known.
+    #[error("{} is not valid UTF-8", field.unwrap_or("string"))]
+    StringNotUtf8 {
+        field: Option<&'static str>,
+        #[source]
+        source: FromUtf8Error,
+    },
+    /// A string is longer than allowed by
+    /// [`ParseOptions::max_string_length`](crate::ParseOptions::max_string_length).
+    #[error("string of {length} bytes exceeds the maximum length of {max} bytes")]
+    StringTooLong { length: u64, max: usize },
+    /// The archive ended while reading data.
+    #[error("archive is truncated in {entry}")]
+    TruncatedData { entry: String },
     /// Invalid TocEntry data was found. This should only happen if the archive is
     /// corrupted (or pgarchive has a bug).
     #[error("format error for id {0}: {1}")]
-    InvalidEntryData(crate::toc::ID, String),
+    InvalidEntryData(crate::toc::DumpId, String),
+    /// A TOC entry or dependency has an id outside the range used by pg_dump.
+    #[error("dump id {0} is out of range")]
+    InvalidDumpId(i64),
     /// Returned when you try to read the data for a
     /// [`TocEntry`](crate::TocEntry), but it has no data.
-    #[error("TOC entry has no data")]
-    NoDataPresent,
+    #[error("error reading data for {desc} {name} (id {id}): TOC entry has no data")]
+    NoDataPresent {
+        id: crate::toc::DumpId,
+        desc: String,
+        /// Name of the entry, including its schema.
+        name: String,
+    },
+    /// Data was requested for a [`TocEntry`](crate::TocEntry) which is not a
+    /// `TABLE DATA` or `BLOBS` entry in the data section, and can never have
+    /// data.
+    #[error("error reading data for {desc} {name} (id {id}): entry is not a data section entry")]
+    NotDataEntry {
+        id: crate::toc::DumpId,
+        desc: String,
+        /// Name of the entry, including its schema.
+        name: String,
+    },
+    /// An IO error occurred while reading the data for a
+    /// [`TocEntry`](crate::TocEntry).
+    #[error("error reading data for {desc} {name} (id {id}): {source}")]
+    ReadDataError {
+        id: crate::toc::DumpId,
+        desc: String,
+        /// Name of the entry, including its schema.
+        name: String,
+        #[source]
+        source: io::Error,
+    },
     /// pgarchive does not support reading blob data.
     #[error("reading BLOB data is not supported")]
     BlobNotSupported,
     /// The archive was made by a pg_dump version that is not supported by this
     /// crate.
-    #[error("archive format {}.{}.{} is not supported", (.0).0, (.0).1, (.0).2)]
+    #[error("archive format {0} is not supported")]
     UnsupportedVersionError(Version),
     /// An unsupported compression method was used for table data.
     #[error("compression method {0} is not supported")]
     CompressionMethodNotSupported(CompressionMethod),
+    /// An error occurred while parsing the archive header or table of contents.
+    ///
+    /// This wraps the underlying error with the byte offset of the field that
+    /// could not be read, and a description of that field. For TOC entries the
+    /// description includes the index of the entry, and its id if that could
+    /// be read.
+    #[error("error parsing {context} at offset {offset}")]
+    ParseError {
+        offset: u64,
+        context: String,
+        #[source]
+        source: Box<ArchiveError>,
+    },
+    /// Table data was requested for an archive read from a stream.
+    #[error("data requires a seekable source")]
+    NotSeekable,
+    /// The archive can not be written, for example because it is a tar
+    /// archive.
+    #[error("writing {0} is not supported")]
+    WriteNotSupported(String),
+    /// The data was compressed using a zstd dictionary, which is not included
+    /// in archives.
+    #[error("dictionary-compressed data not supported (dictionary id {0})")]
+    DictionaryNotSupported(u32),
 }
 
-pub type Oid = u64;
+impl ArchiveError {
+    pub(crate) fn not_data_entry(entry: &TocEntry) -> ArchiveError {
+        ArchiveError::NotDataEntry {
+            id: entry.id,
+            desc: entry.desc.clone(),
+            name: entry.qualified_name(),
+        }
+    }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+    pub(crate) fn no_data(entry: &TocEntry) -> ArchiveError {
+        ArchiveError::NoDataPresent {
+            id: entry.id,
+            desc: entry.desc.clone(),
+            name: entry.qualified_name(),
+        }
+    }
+
+    /// Return the underlying error, without the position information added
+    /// by [`ArchiveError::ParseError`].
+    ///
+    /// This is useful to check for a specific problem:
+    ///
+    /// ```rust
+    /// # use pgarchive::{Archive, ArchiveError};
+    /// match Archive::parse(&mut &b"not an archive"[..]) {
+    ///     Err(e) if matches!(e.root_cause(), ArchiveError::BadMagic) => {
+    ///         println!("not a pg_dump archive")
+    ///     }
+    ///     _ => (),
+    /// }
+    /// ```
+    pub fn root_cause(&self) -> &ArchiveError {
+        match self {
+            ArchiveError::ParseError { source, .. } => source.root_cause(),
+            e => e,
+        }
+    }
+}
+
+/// IO errors which wrap an [`ArchiveError`], such as the errors returned by
+/// the readers from [`Archive::read_data`](crate::Archive::read_data), are
+/// unwrapped.
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        e.downcast::<ArchiveError>()
+            .unwrap_or_else(ArchiveError::IOError)
+    }
+}
+
+/// A problem found while parsing an archive which did not stop parsing.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseWarning {
+    /// The type of problem.
+    pub kind: WarningKind,
+    /// Description of the problem.
+    pub message: String,
+    /// Byte offset in the archive of the field with the problem.
+    pub offset: u64,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+/// Types of [`ParseWarning`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum WarningKind {
+    /// The archive format is newer than the supported formats, and was read
+    /// using the layout of the most recent supported format.
+    UnsupportedVersion,
+    /// The daylight saving time flag of the creation date is not 0 or 1.
+    InvalidDst,
+    /// The database name is empty.
+    EmptyDatabaseName,
+    /// A TOC entry depends on id 0, which is never used. The dependency is
+    /// ignored.
+    InvalidDependency,
+    /// The creation date in the header is not valid, and was replaced by
+    /// the Unix epoch.
+    InvalidCreateDate,
+}
+
+/// PostgreSQL object identifier.
+///
+/// OIDs are unsigned 32 bit integers. Many TOC entries do not refer to a
+/// database object and use the [invalid OID](Oid::INVALID) instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Oid(pub(crate) u32);
+
+impl Oid {
+    /// The OID used when an entry does not refer to an object.
+    pub const INVALID: Oid = Oid(0);
+
+    /// Create an OID from its number.
+    pub const fn new(oid: u32) -> Oid {
+        Oid(oid)
+    }
+
+    /// Return the OID as a number.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+
+    /// Return true if this is the [invalid OID](Oid::INVALID).
+    pub fn is_invalid(&self) -> bool {
+        *self == Oid::INVALID
+    }
+}
+
+impl From<u32> for Oid {
+    fn from(value: u32) -> Oid {
+        Oid(value)
+    }
+}
+
+impl From<Oid> for u32 {
+    fn from(oid: Oid) -> u32 {
+        oid.0
+    }
+}
+
+impl fmt::Display for Oid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Error returned when parsing an invalid OID.
+#[derive(Error, Debug, PartialEq)]
+#[error("invalid OID \"{0}\", expected a number between 0 and 4294967295")]
+pub struct ParseOidError(String);
+
+impl FromStr for Oid {
+    type Err = ParseOidError;
+
+    /// Parse an OID in decimal notation.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Oid).map_err(|_| ParseOidError(s.into()))
+    }
+}
+
+/// Location of the data for a TOC entry.
+///
+/// For tar archives the position is the start of the tar member holding the
+/// data.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Offset {
+    /// The offset state is not known.
+    ///
+    /// Archives from before format 1.7 did not store an offset state. This
+    /// crate does not support those versions, so this only occurs for a flag
+    /// value of 0, which pg_dump never writes.
     Unknown,
+    /// The entry has data, but its position was not recorded. pg_dump does
+    /// this when the archive is written to a pipe, so it can not go back and
+    /// update the table of contents.
     PosNotSet,
+    /// The entry has data starting at the given file offset.
     PosSet(u64),
+    /// The entry has no data, for example because it only creates a schema
+    /// object.
     NoData,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+impl Offset {
+    /// Return the file offset of the data, if it is known.
+    pub fn pos(&self) -> Option<u64> {
+        match self {
+            Offset::PosSet(pos) => Some(*pos),
+            _ => None,
+        }
+    }
+
+    /// Return true if the entry has data, even if its position is not known.
+    pub fn has_data(&self) -> bool {
+        matches!(self, Offset::PosSet(_) | Offset::PosNotSet)
+    }
+}
+
+impl fmt::Display for Offset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Offset::Unknown => f.write_str("unknown"),
+            Offset::PosNotSet => f.write_str("not recorded"),
+            Offset::PosSet(pos) => write!(f, "@{:#x}", pos),
+            Offset::NoData => f.write_str("no data"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum BlockType {
     Data = 1,
@@ -67,39 +468,155 @@ impl TryFrom<u8> for BlockType {
     }
 }
 
+/// Archive formats written by pg_dump which store a binary table of contents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum ArchiveFormat {
+    /// Custom format (`pg_dump -Fc`), with data stored after the TOC.
+    #[default]
+    Custom = 1,
+    /// Tar format (`pg_dump -Ft`), with data stored in separate tar members.
+    Tar = 3,
+}
+
+impl TryFrom<u8> for ArchiveFormat {
+    type Error = ();
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            x if x == ArchiveFormat::Custom as u8 => Ok(ArchiveFormat::Custom),
+            x if x == ArchiveFormat::Tar as u8 => Ok(ArchiveFormat::Tar),
+            _ => Err(()),
+        }
+    }
+}
+
 /// Possible compression methods used for data.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CompressionMethod {
     /// Data is not compressed
     None,
-    /// Data is compressed using gzip, with the given compress level (1..9)
+    /// Data is compressed using gzip, with the given compress level (1..9, or
+    /// -1 for the zlib default level).
+    ///
+    /// Despite the name the data is stored as a zlib stream, not in the gzip
+    /// file format. Options in a compression specification such as
+    /// `gzip:level=9` only affect the compression level and do not change
+    /// how the data is stored. For newer archives the level is not recorded
+    /// and is always 0.
     Gzip(i64),
     /// Data is compressed using [LZ4](https://lz4.org).
     LZ4,
-    /// Data is compressed using DEFLATE.
+    /// Data is compressed using [Zstandard](https://facebook.github.io/zstd/).
     ZSTD,
+    /// A compression method added in a newer PostgreSQL version. The table
+    /// of contents can be read, but data can not be decompressed.
+    Unknown(u8),
 }
 
+/// Convert the compression method id stored in the archive header.
+///
+/// Unknown ids are returned as an error. Use [`CompressionMethod::from_id`]
+/// instead, which returns them as [`CompressionMethod::Unknown`].
 impl TryFrom<u8> for CompressionMethod {
     type Error = ();
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
-        match value {
-            0 => Ok(CompressionMethod::None),
-            1 => Ok(CompressionMethod::Gzip(0)),
-            2 => Ok(CompressionMethod::LZ4),
-            3 => Ok(CompressionMethod::ZSTD),
-            _ => Err(()),
+        match CompressionMethod::from_id(value) {
+            CompressionMethod::Unknown(_) => Err(()),
+            method => Ok(method),
         }
     }
 }
 
+impl CompressionMethod {
+    /// Return the compression method for an id stored in the archive header.
+    ///
+    /// Ids which are not known are returned as [`CompressionMethod::Unknown`].
+    pub fn from_id(id: u8) -> CompressionMethod {
+        match id {
+            0 => CompressionMethod::None,
+            1 => CompressionMethod::Gzip(0),
+            2 => CompressionMethod::LZ4,
+            3 => CompressionMethod::ZSTD,
+            id => CompressionMethod::Unknown(id),
+        }
+    }
+
+    /// Return the id stored in the archive header for the compression method.
+    ///
+    /// This is the reverse of [`CompressionMethod::from_id`].
+    pub(crate) fn id(&self) -> u8 {
+        match self {
+            CompressionMethod::None => 0,
+            CompressionMethod::Gzip(_) => 1,
+            CompressionMethod::LZ4 => 2,
+            CompressionMethod::ZSTD => 3,
+            CompressionMethod::Unknown(id) => *id,
+        }
+    }
+
+    /// Return the name of the compression method as used by the `--compress`
+    /// option of pg_dump.
+    ///
+    /// The compression level is not included.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompressionMethod::None => "none",
+            CompressionMethod::Gzip(_) => "gzip",
+            CompressionMethod::LZ4 => "lz4",
+            CompressionMethod::ZSTD => "zstd",
+            CompressionMethod::Unknown(_) => "unknown",
+        }
+    }
+}
+
+impl From<CompressionMethod> for &'static str {
+    fn from(method: CompressionMethod) -> &'static str {
+        method.as_str()
+    }
+}
+
 impl fmt::Display for CompressionMethod {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
 
+/// Format used to store the data of a TOC entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DataFormat {
+    /// Data is stored in `COPY` text format, to be used with the `COPY`
+    /// statement of the TOC entry.
+    Copy,
+    /// Data is stored as `INSERT` statements. This is used when the archive
+    /// is created with `pg_dump --inserts` or `--column-inserts`.
+    Inserts,
+    /// Data holds the contents of large objects, as written for `BLOBS`
+    /// entries.
+    Blobs,
+    /// The entry has data in a format which is not known.
+    Unknown,
+    /// The entry has no data.
+    None,
+}
+
+/// Whether the data for a TOC entry can be located.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataAvailability {
+    /// The entry has data at a known position.
+    Present,
+    /// The entry has no data.
+    NoData,
+    /// The entry may have data, but its position was not recorded.
+    PositionUnknown,
+}
+
 /// Enumeration of table of contents section types.
 ///
 /// Each entry in the table of contents is associate with a section, which
@@ -109,7 +626,9 @@ impl fmt::Display for CompressionMethod {
 /// 1. PreData
 /// 1. Data
 /// 1. PostData
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
 #[repr(u8)]
 pub enum Section {
     /// Used for table of contents entries that do not modify the schema or add
@@ -139,8 +658,420 @@ impl TryFrom<i64> for Section {
     }
 }
 
+impl Section {
+    /// All sections, in the order they are restored.
+    pub const ALL: [Section; 4] = [
+        Section::None,
+        Section::PreData,
+        Section::Data,
+        Section::PostData,
+    ];
+
+    /// Return the name of the section as used by the `--section` option of
+    /// pg_restore.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Section::None => "none",
+            Section::PreData => "pre-data",
+            Section::Data => "data",
+            Section::PostData => "post-data",
+        }
+    }
+
+    /// Return the name of the section as used on the command line of
+    /// pg_restore. This is the same as [`as_str`](Section::as_str).
+    pub fn as_cli_str(&self) -> &'static str {
+        self.as_str()
+    }
+
+    /// Parse a section name as used on the command line of pg_restore.
+    ///
+    /// Unlike [`Section::from_str`] this only accepts the exact names
+    /// returned by [`as_cli_str`](Section::as_cli_str), like pg_restore does.
+    pub fn from_cli_str(s: &str) -> Option<Section> {
+        Section::ALL
+            .into_iter()
+            .find(|section| section.as_cli_str() == s)
+    }
+}
+
 impl fmt::Display for Section {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self)
+        f.write_str(self.as_str())
+    }
+}
+
+/// Error returned when parsing an invalid section name.
+#[derive(Error, Debug, PartialEq)]
+#[error("invalid section \"{0}\", valid values are: none, pre-data, data, post-data")]
+pub struct ParseSectionError(String);
+
+impl FromStr for Section {
+    type Err = ParseSectionError;
+
+    /// Parse a section name as used by the `--section` option of pg_restore.
+    ///
+    /// The variant names, such as `PreData`, are accepted as well. Case is
+    /// ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Section::ALL
+            .into_iter()
+            .find(|section| {
+                s.eq_ignore_ascii_case(section.as_str())
+                    || s.eq_ignore_ascii_case(&format!("{:?}", section))
+            })
+            .ok_or_else(|| ParseSectionError(s.into()))
+    }
+}
+
+/// The type of object described by a TOC entry.
+///
+/// pg_dump stores the object type as a string in [`TocEntry::desc`]. Use
+/// [`TocEntry::object_type`] to get it as an `ObjectType`. Object types this
+/// crate does not know about are returned as [`ObjectType::Other`], so
+/// converting from a string never fails:
+///
+/// ```rust
+/// # use pgarchive::ObjectType;
+/// assert_eq!("TABLE DATA".parse(), Ok(ObjectType::TableData));
+/// assert_eq!(ObjectType::TableData.as_str(), "TABLE DATA");
+/// assert_eq!("OPERATOR".parse(), Ok(ObjectType::Other("OPERATOR".into())));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ObjectType {
+    Table,
+    TableData,
+    Sequence,
+    SequenceSet,
+    Index,
+    Constraint,
+    FkConstraint,
+    CheckConstraint,
+    Trigger,
+    Rule,
+    View,
+    MaterializedView,
+    Function,
+    Schema,
+    Extension,
+    Comment,
+    Acl,
+    DefaultAcl,
+    Blob,
+    Blobs,
+    BlobMetadata,
+    Database,
+    DatabaseProperties,
+    Encoding,
+    StdStrings,
+    SearchPath,
+    Default,
+    Tablespace,
+    /// Any other object type, with the descriptor as used by pg_dump.
+    Other(String),
+}
+
+impl ObjectType {
+    /// Return the descriptor pg_dump uses for this object type.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ObjectType::Table => "TABLE",
+            ObjectType::TableData => "TABLE DATA",
+            ObjectType::Sequence => "SEQUENCE",
+            ObjectType::SequenceSet => "SEQUENCE SET",
+            ObjectType::Index => "INDEX",
+            ObjectType::Constraint => "CONSTRAINT",
+            ObjectType::FkConstraint => "FK CONSTRAINT",
+            ObjectType::CheckConstraint => "CHECK CONSTRAINT",
+            ObjectType::Trigger => "TRIGGER",
+            ObjectType::Rule => "RULE",
+            ObjectType::View => "VIEW",
+            ObjectType::MaterializedView => "MATERIALIZED VIEW",
+            ObjectType::Function => "FUNCTION",
+            ObjectType::Schema => "SCHEMA",
+            ObjectType::Extension => "EXTENSION",
+            ObjectType::Comment => "COMMENT",
+            ObjectType::Acl => "ACL",
+            ObjectType::DefaultAcl => "DEFAULT ACL",
+            ObjectType::Blob => "BLOB",
+            ObjectType::Blobs => "BLOBS",
+            ObjectType::BlobMetadata => "BLOB METADATA",
+            ObjectType::Database => "DATABASE",
+            ObjectType::DatabaseProperties => "DATABASE PROPERTIES",
+            ObjectType::Encoding => "ENCODING",
+            ObjectType::StdStrings => "STDSTRINGS",
+            ObjectType::SearchPath => "SEARCHPATH",
+            ObjectType::Default => "DEFAULT",
+            ObjectType::Tablespace => "TABLESPACE",
+            ObjectType::Other(desc) => desc,
+        }
+    }
+
+    /// Return the object type for a descriptor, or `None` for descriptors
+    /// this crate does not know about.
+    ///
+    /// Unlike converting to an `ObjectType`, this never allocates.
+    pub(crate) fn known(desc: &str) -> Option<ObjectType> {
+        Some(match desc {
+            "TABLE" => ObjectType::Table,
+            "TABLE DATA" => ObjectType::TableData,
+            "SEQUENCE" => ObjectType::Sequence,
+            "SEQUENCE SET" => ObjectType::SequenceSet,
+            "INDEX" => ObjectType::Index,
+            "CONSTRAINT" => ObjectType::Constraint,
+            "FK CONSTRAINT" => ObjectType::FkConstraint,
+            "CHECK CONSTRAINT" => ObjectType::CheckConstraint,
+            "TRIGGER" => ObjectType::Trigger,
+            "RULE" => ObjectType::Rule,
+            "VIEW" => ObjectType::View,
+            "MATERIALIZED VIEW" => ObjectType::MaterializedView,
+            "FUNCTION" => ObjectType::Function,
+            "SCHEMA" => ObjectType::Schema,
+            "EXTENSION" => ObjectType::Extension,
+            "COMMENT" => ObjectType::Comment,
+            "ACL" => ObjectType::Acl,
+            "DEFAULT ACL" => ObjectType::DefaultAcl,
+            "BLOB" => ObjectType::Blob,
+            "BLOBS" => ObjectType::Blobs,
+            "BLOB METADATA" => ObjectType::BlobMetadata,
+            "DATABASE" => ObjectType::Database,
+            "DATABASE PROPERTIES" => ObjectType::DatabaseProperties,
+            "ENCODING" => ObjectType::Encoding,
+            "STDSTRINGS" => ObjectType::StdStrings,
+            "SEARCHPATH" => ObjectType::SearchPath,
+            "DEFAULT" => ObjectType::Default,
+            "TABLESPACE" => ObjectType::Tablespace,
+            _ => return None,
+        })
+    }
+}
+
+impl From<&str> for ObjectType {
+    fn from(desc: &str) -> Self {
+        ObjectType::known(desc).unwrap_or_else(|| ObjectType::Other(desc.into()))
+    }
+}
+
+impl FromStr for ObjectType {
+    type Err = std::convert::Infallible;
+
+    /// Parse a pg_dump object descriptor. Unknown descriptors are returned
+    /// as [`ObjectType::Other`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ObjectType::from(s))
+    }
+}
+
+impl fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn object_type_round_trip() {
+        let descs = [
+            "TABLE",
+            "TABLE DATA",
+            "SEQUENCE",
+            "SEQUENCE SET",
+            "INDEX",
+            "CONSTRAINT",
+            "FK CONSTRAINT",
+            "CHECK CONSTRAINT",
+            "TRIGGER",
+            "RULE",
+            "VIEW",
+            "MATERIALIZED VIEW",
+            "FUNCTION",
+            "SCHEMA",
+            "EXTENSION",
+            "COMMENT",
+            "ACL",
+            "DEFAULT ACL",
+            "BLOB",
+            "BLOBS",
+            "BLOB METADATA",
+            "DATABASE",
+            "DATABASE PROPERTIES",
+            "ENCODING",
+            "STDSTRINGS",
+            "SEARCHPATH",
+            "DEFAULT",
+            "TABLESPACE",
+        ];
+        for desc in descs {
+            let object_type: ObjectType = desc.parse().unwrap();
+            assert!(
+                !matches!(object_type, ObjectType::Other(_)),
+                "{desc} is not known"
+            );
+            assert_eq!(object_type.as_str(), desc);
+            assert_eq!(object_type.to_string(), desc);
+        }
+        assert_eq!(ObjectType::from("TABLE DATA"), ObjectType::TableData);
+        assert_eq!(ObjectType::from("FK CONSTRAINT"), ObjectType::FkConstraint);
+    }
+
+    #[test]
+    fn object_type_other() {
+        let object_type: ObjectType = "OPERATOR CLASS".parse().unwrap();
+        assert_eq!(object_type, ObjectType::Other("OPERATOR CLASS".into()));
+        assert_eq!(object_type.as_str(), "OPERATOR CLASS");
+        // Descriptors are case sensitive, as in pg_dump.
+        assert_eq!(ObjectType::from("table"), ObjectType::Other("table".into()));
+        assert_eq!(ObjectType::known("OPERATOR CLASS"), None);
+        assert_eq!(ObjectType::known("TABLE"), Some(ObjectType::Table));
+    }
+
+    #[test]
+    fn version_from_str() {
+        assert_eq!("1.16".parse(), Ok(Version::new(1, 16, 0)));
+        assert_eq!("1.16.2".parse(), Ok(Version::new(1, 16, 2)));
+        assert!("1".parse::<Version>().is_err());
+        assert!("1.16.0.1".parse::<Version>().is_err());
+        assert!("1.x".parse::<Version>().is_err());
+        assert!("1.256".parse::<Version>().is_err());
+    }
+
+    #[test]
+    fn version_ordering() {
+        let version = Version::new(1, 14, 0);
+        assert!(version < Version::new(1, 15, 0));
+        assert!(version > Version::new(1, 13, 2));
+        assert!(version < Version::new(2, 0, 0));
+        assert_eq!(version, (1, 14, 0));
+        assert!(version >= (1, 14, 0));
+        assert!(version >= "1.14");
+        assert!(version < "1.14.1");
+        assert_eq!(version, "1.14.0");
+        assert_ne!(version, "bogus");
+    }
+
+    #[test]
+    fn version_error_message() {
+        assert_eq!(
+            ArchiveError::UnsupportedVersionError(Version::new(1, 17, 0)).to_string(),
+            "archive format 1.17.0 is not supported"
+        );
+    }
+
+    #[test]
+    fn section_from_str() {
+        assert_eq!("none".parse(), Ok(Section::None));
+        assert_eq!("pre-data".parse(), Ok(Section::PreData));
+        assert_eq!("data".parse(), Ok(Section::Data));
+        assert_eq!("post-data".parse(), Ok(Section::PostData));
+        assert_eq!("None".parse(), Ok(Section::None));
+        assert_eq!("PreData".parse(), Ok(Section::PreData));
+        assert_eq!("DATA".parse(), Ok(Section::Data));
+        assert_eq!("postdata".parse(), Ok(Section::PostData));
+        assert_eq!("Post-Data".parse(), Ok(Section::PostData));
+    }
+
+    #[test]
+    fn section_as_str() {
+        for section in Section::ALL {
+            assert_eq!(section.as_str().parse(), Ok(section));
+            assert_eq!(section.to_string(), section.as_str());
+        }
+        assert_eq!(Section::PreData.as_str(), "pre-data");
+        assert_eq!(Section::PostData.to_string(), "post-data");
+    }
+
+    #[test]
+    fn section_cli_str() {
+        for section in Section::ALL {
+            assert_eq!(Section::from_cli_str(section.as_cli_str()), Some(section));
+        }
+        assert_eq!(Section::PreData.as_cli_str(), "pre-data");
+        assert_eq!(Section::from_cli_str("post-data"), Some(Section::PostData));
+        assert_eq!(Section::from_cli_str("PostData"), None);
+        assert_eq!(Section::from_cli_str("Data"), None);
+    }
+
+    #[test]
+    fn offset_display() {
+        assert_eq!(Offset::Unknown.to_string(), "unknown");
+        assert_eq!(Offset::PosNotSet.to_string(), "not recorded");
+        assert_eq!(Offset::PosSet(0x16d7).to_string(), "@0x16d7");
+        assert_eq!(Offset::NoData.to_string(), "no data");
+    }
+
+    #[test]
+    fn offset_pos() {
+        assert_eq!(Offset::PosSet(0x16d7).pos(), Some(0x16d7));
+        assert_eq!(Offset::PosNotSet.pos(), None);
+        assert_eq!(Offset::NoData.pos(), None);
+        assert_eq!(Offset::Unknown.pos(), None);
+        assert!(Offset::PosSet(0).has_data());
+        assert!(Offset::PosNotSet.has_data());
+        assert!(!Offset::NoData.has_data());
+        assert!(!Offset::Unknown.has_data());
+    }
+
+    #[test]
+    fn compression_method_as_str() {
+        assert_eq!(CompressionMethod::None.as_str(), "none");
+        assert_eq!(CompressionMethod::Gzip(9).as_str(), "gzip");
+        assert_eq!(CompressionMethod::LZ4.as_str(), "lz4");
+        let name: &str = CompressionMethod::ZSTD.into();
+        assert_eq!(name, "zstd");
+        assert_eq!(CompressionMethod::Unknown(4).as_str(), "unknown");
+    }
+
+    #[test]
+    fn compression_method_from_id() {
+        assert_eq!(CompressionMethod::from_id(3), CompressionMethod::ZSTD);
+        assert_eq!(CompressionMethod::from_id(9), CompressionMethod::Unknown(9));
+        assert_eq!(
+            CompressionMethod::try_from(1),
+            Ok(CompressionMethod::Gzip(0))
+        );
+        assert_eq!(CompressionMethod::try_from(9), Err(()));
+        for id in 0..=9 {
+            assert_eq!(CompressionMethod::from_id(id).id(), id);
+        }
+    }
+
+    #[test]
+    fn oid_from_str() {
+        assert_eq!("0".parse(), Ok(Oid::INVALID));
+        assert_eq!("33686".parse(), Ok(Oid(33686)));
+        assert_eq!("4294967295".parse(), Ok(Oid(u32::MAX)));
+        assert!("4294967296".parse::<Oid>().is_err());
+        assert!("-1".parse::<Oid>().is_err());
+        assert!("".parse::<Oid>().is_err());
+        assert_eq!(Oid(33686).to_string(), "33686");
+        assert!(Oid(0).is_invalid());
+        assert!(!Oid(1).is_invalid());
+        assert_eq!(Oid::new(33686).get(), 33686);
+    }
+
+    #[test]
+    fn section_as_hash_key() {
+        let mut counts: HashMap<Section, usize> = HashMap::new();
+        for section in [Section::PreData, Section::Data, Section::PreData] {
+            *counts.entry(section).or_default() += 1;
+        }
+        assert_eq!(counts[&Section::PreData], 2);
+        assert_eq!(counts[&Section::Data], 1);
+        assert!(!counts.contains_key(&Section::PostData));
+    }
+
+    #[test]
+    fn section_from_str_invalid() {
+        let err = "pre_data".parse::<Section>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid section \"pre_data\", valid values are: none, pre-data, data, post-data"
+        );
     }
 }
@@ -0,0 +1,74 @@
+//! Archive format versions, and the features they introduced.
+//!
+//! ```rust
+//! use std::fs::File;
+//! use pgarchive::{versions, Archive};
+//!
+//! let archive = Archive::parse(&mut File::open("tests/test.pgdump").unwrap()).unwrap();
+//! if archive.version >= versions::K_VERS_1_14 {
+//!     println!("this archive records table access methods");
+//! }
+//! ```
+use crate::types::Version;
+
+// Historical version numbers are described in `postgres/src/bin/pg_dump/pg_backup_archiver.h`
+
+/// PostgreSQL 8.0 - add tablespace.
+pub const K_VERS_1_10: Version = Version::new(1, 10, 0);
+
+/// PostgreSQL 8.4 - add toc section indicator.
+pub const K_VERS_1_11: Version = Version::new(1, 11, 0);
+
+/// PostgreSQL 9.0 - add separate BLOB entries.
+pub const K_VERS_1_12: Version = Version::new(1, 12, 0);
+
+/// PostgreSQL 11 - change search_path behavior.
+pub const K_VERS_1_13: Version = Version::new(1, 13, 0);
+
+/// PostgreSQL 12 - add tableam.
+pub const K_VERS_1_14: Version = Version::new(1, 14, 0);
+
+/// PostgreSQL 16 - add compression_algorithm in header.
+pub const K_VERS_1_15: Version = Version::new(1, 15, 0);
+
+/// PostgreSQL 17 - BLOB METADATA entries and multiple BLOBS, relkind.
+pub const K_VERS_1_16: Version = Version::new(1, 16, 0);
+
+/// Features of the archive format which depend on the format version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FormatFeature {
+    /// TOC entries include a tablespace.
+    Tablespace,
+    /// TOC entries include a [section](crate::Section).
+    Section,
+    /// Large objects are stored as separate BLOB entries.
+    BlobEntries,
+    /// The search path is set by a `SEARCHPATH` entry instead of for each
+    /// object.
+    SearchPathEntry,
+    /// TOC entries include a table access method.
+    TableAccessMethod,
+    /// The header stores the compression method as a single byte, instead of
+    /// a zlib compression level.
+    CompressionMethodByte,
+    /// TOC entries include the relation kind.
+    RelKind,
+    /// Large objects are described by `BLOB METADATA` entries.
+    BlobMetadata,
+}
+
+impl FormatFeature {
+    /// Return the first format version with this feature.
+    pub fn since(&self) -> Version {
+        match self {
+            FormatFeature::Tablespace => K_VERS_1_10,
+            FormatFeature::Section => K_VERS_1_11,
+            FormatFeature::BlobEntries => K_VERS_1_12,
+            FormatFeature::SearchPathEntry => K_VERS_1_13,
+            FormatFeature::TableAccessMethod => K_VERS_1_14,
+            FormatFeature::CompressionMethodByte => K_VERS_1_15,
+            FormatFeature::RelKind | FormatFeature::BlobMetadata => K_VERS_1_16,
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::archive::Archive;
+use crate::types::{ArchiveError, ArchiveFormat, CompressionMethod, Offset};
+use crate::versions::{K_VERS_1_10, K_VERS_1_15, K_VERS_1_16};
+use std::io::{self, Read, Seek, Write};
+
+impl Archive {
+    /// Write the archive in the custom format.
+    ///
+    /// The header and table of contents are written from this archive, so
+    /// changes made to [`toc_entries`](Archive::toc_entries) are included.
+    /// The data is copied from `src` as stored, without decompressing it, so
+    /// `src` must be the file the archive was parsed from. The archive is
+    /// written using the same format version, and the data of the entries is
+    /// written in TOC order.
+    ///
+    /// Only custom format archives can be written. Data which was written
+    /// without recording its position can not be copied, and returns
+    /// [`ArchiveError::NoDataPresent`].
+    ///
+    /// ```rust
+    /// # use pgarchive::Archive;
+    /// # fn main() -> Result<(), pgarchive::ArchiveError> {
+    /// let (mut archive, file) = Archive::open("tests/test.pgdump")?;
+    /// archive.database_name = String::from("pizzeria");
+    /// let mut out = Vec::new();
+    /// archive.write(file, &mut out)?;
+    /// assert_eq!(Archive::parse(&mut &out[..])?.database_name, "pizzeria");
+    /// #     Ok(())
+    /// # }
+    /// ```
+    pub fn write<R: Read + Seek, W: Write>(
+        &self,
+        mut src: R,
+        mut out: W,
+    ) -> Result<(), ArchiveError> {
+        if self.io_config.format != ArchiveFormat::Custom {
+            return Err(ArchiveError::WriteNotSupported("tar archives".into()));
+        }
+        if self.version < K_VERS_1_10 || self.version > K_VERS_1_16 {
+            return Err(ArchiveError::UnsupportedVersionError(self.version));
+        }
+
+        // Find the data blocks to copy. A block ends where the next block
+        // starts, or at the end of the file.
+        let mut blocks = Vec::new();
+        for entry in &self.toc_entries {
+            match entry.offset {
+                Offset::PosSet(start) => {
+                    if !self.seekable {
+                        return Err(ArchiveError::NotSeekable);
+                    }
+                    let end = match self.data_end(entry) {
+                        Some(end) => end,
+                        None => src.seek(io::SeekFrom::End(0))?,
+                    };
+                    let size = end.checked_sub(start).ok_or_else(|| {
+                        ArchiveError::InvalidData("data starts after the end of the file".into())
+                    })?;
+                    blocks.push((start, size));
+                }
+                Offset::PosNotSet | Offset::Unknown if entry.had_dumper => {
+                    return Err(ArchiveError::no_data(entry));
+                }
+                _ => (),
+            }
+        }
+
+        // The size of the table of contents does not depend on the offsets
+        // in it, so it can be determined before the offsets are known.
+        let mut offsets: Vec<Offset> = self.toc_entries.iter().map(|e| e.offset).collect();
+        let mut header = Vec::new();
+        self.write_header(&mut header)?;
+        self.write_toc(&mut header, &offsets)?;
+        let mut position = header.len() as u64;
+        let mut sizes = blocks.iter().map(|&(_, size)| size);
+        for offset in &mut offsets {
+            if let Offset::PosSet(_) = offset {
+                *offset = Offset::PosSet(position);
+                position += sizes.next().unwrap_or(0);
+            }
+        }
+        header.clear();
+        self.write_header(&mut header)?;
+        self.write_toc(&mut header, &offsets)?;
+        out.write_all(&header)?;
+
+        for (start, size) in blocks {
+            src.seek(io::SeekFrom::Start(start))?;
+            let copied = io::copy(&mut (&mut src).take(size), &mut out)?;
+            if copied != size {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+        }
+        out.flush()?;
+        Ok(())
+    }
+
+    /// Write the archive header, up to the table of contents.
+    fn write_header(&self, f: &mut Vec<u8>) -> Result<(), ArchiveError> {
+        // Check `WriteHead` in `postgres/src/bin/pg_dump/pg_backup_archiver.c`
+        let cfg = &self.io_config;
+        f.extend_from_slice(b"PGDMP");
+        f.extend_from_slice(&[self.version.major, self.version.minor, self.version.patch]);
+        cfg.write_byte(f, cfg.int_size as u8)?;
+        cfg.write_byte(f, cfg.offset_size as u8)?;
+        cfg.write_byte(f, cfg.format as u8)?;
+        if self.version >= K_VERS_1_15 {
+            cfg.write_byte(f, self.compression_method.id())?;
+        } else {
+            let level = match self.compression_method {
+                CompressionMethod::None => 0,
+                // Newer formats do not record the level, and 0 means no
+                // compression for older formats.
+                CompressionMethod::Gzip(0) => -1,
+                CompressionMethod::Gzip(level) => level,
+                method => return Err(ArchiveError::CompressionMethodNotSupported(method)),
+            };
+            cfg.write_int(f, level)?;
+        }
+        let date = &self.raw_create_date;
+        for value in [
+            date.sec, date.min, date.hour, date.mday, date.mon, date.year, date.isdst,
+        ] {
+            cfg.write_int(f, value)?;
+        }
+        cfg.write_string(f, &self.database_name)?;
+        cfg.write_string(f, &self.server_version)?;
+        cfg.write_string(f, &self.pgdump_version)
+    }
+
+    /// Write the table of contents, using `offsets` for the data of the
+    /// entries.
+    fn write_toc(&self, f: &mut Vec<u8>, offsets: &[Offset]) -> Result<(), ArchiveError> {
+        let cfg = &self.io_config;
+        cfg.write_int(f, self.toc_entries.len() as i64)?;
+        for (entry, &offset) in self.toc_entries.iter().zip(offsets) {
+            let relkind = self.relkinds.get(&entry.id).copied().unwrap_or(0);
+            entry.write(f, cfg, self.version, relkind, offset)?;
+        }
+        Ok(())
+    }
+}
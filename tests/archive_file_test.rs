@@ -0,0 +1,139 @@
+mod common;
+
+use common::{build_archive, write_archive, Entry};
+#[cfg(feature = "gzip")]
+use pgarchive::{Archive, Section};
+use pgarchive::{ArchiveError, ArchiveFile};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+fn fixture() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump")
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_archive_file() -> Result<(), ArchiveError> {
+    let archive = ArchiveFile::open(fixture())?;
+    let (expected, mut f) = Archive::open(fixture())?;
+    assert_eq!(archive.archive(), &expected);
+
+    let entry = archive
+        .find_toc_entry(Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    assert_eq!(archive.find_toc_entry_by_id(entry.id), Some(entry));
+
+    let mut data = Vec::new();
+    archive.read_data(entry)?.read_to_end(&mut data)?;
+    let mut expected_data = Vec::new();
+    expected
+        .read_data(&mut f, entry)?
+        .read_to_end(&mut expected_data)?;
+    assert_eq!(data, expected_data);
+
+    // Readers only borrow the archive, so several can be created in turn.
+    assert_eq!(
+        archive.with_data(entry, |r| std::io::copy(r, &mut std::io::sink()))??,
+        data.len() as u64
+    );
+    assert_eq!(archive.copy_lines(entry)?.count(), 5);
+    assert_eq!(
+        archive.table_data_size(entry)?,
+        expected.table_data_size(&mut f, entry)?
+    );
+    assert!(archive
+        .restore_sql_for_entry(entry)?
+        .ends_with(&*String::from_utf8_lossy(&data)));
+
+    let (archive, _) = archive.into_parts();
+    assert_eq!(archive, expected);
+    Ok(())
+}
+
+#[test]
+fn test_archive_file_concurrent_readers() -> Result<(), ArchiveError> {
+    // The tables are larger than the read buffer, so readers have to go back
+    // to the file while the other readers are in use.
+    let tables: Vec<Vec<Vec<u8>>> = (b'a'..=b'c')
+        .map(|c| (0..100).map(|i| vec![c; 500 + i]).collect())
+        .collect();
+    let entries: Vec<Entry> = tables
+        .iter()
+        .zip(1..)
+        .map(|(chunks, id)| Entry::table_data(id, "pizza", chunks.clone()))
+        .collect();
+    let file = write_archive(&build_archive(0, &entries));
+    let archive = ArchiveFile::open(file.path())?;
+    let entries = &archive.archive().toc_entries;
+    let expected: Vec<Vec<u8>> = tables.iter().map(|chunks| chunks.concat()).collect();
+
+    // Read from all readers in turn, a few bytes at a time.
+    let mut readers = entries
+        .iter()
+        .map(|entry| archive.read_data(entry))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut data = vec![Vec::new(); readers.len()];
+    loop {
+        let mut done = true;
+        for (reader, data) in readers.iter_mut().zip(&mut data) {
+            let mut buffer = [0; 1000];
+            let size = reader.read(&mut buffer)?;
+            data.extend_from_slice(&buffer[..size]);
+            done &= size == 0;
+        }
+        if done {
+            break;
+        }
+    }
+    assert_eq!(data, expected);
+
+    // The archive can be shared between threads.
+    std::thread::scope(|s| {
+        let handles: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                let archive = &archive;
+                s.spawn(move || -> Result<Vec<u8>, ArchiveError> {
+                    let mut data = Vec::new();
+                    archive.read_data(entry)?.read_to_end(&mut data)?;
+                    Ok(data)
+                })
+            })
+            .collect();
+        for (handle, expected) in handles.into_iter().zip(&expected) {
+            assert_eq!(&handle.join().unwrap()?, expected);
+        }
+        Ok(())
+    })
+}
+
+#[test]
+#[cfg(unix)]
+fn test_archive_file_replaced() -> Result<(), ArchiveError> {
+    let file = write_archive(&build_archive(
+        0,
+        &[Entry::table_data(1, "pizza", vec![b"margherita".to_vec()])],
+    ));
+    let archive = ArchiveFile::open(file.path())?;
+
+    // Data is read from the file which was parsed, not from the file which
+    // is at its path now.
+    let other = write_archive(&build_archive(
+        0,
+        &[Entry::table_data(1, "pizza", vec![b"hawaii".to_vec()])],
+    ));
+    std::fs::rename(other.path(), file.path())?;
+    let entry = &archive.archive().toc_entries[0];
+    let mut data = Vec::new();
+    archive.read_data(entry)?.read_to_end(&mut data)?;
+    assert_eq!(data, b"margherita");
+    Ok(())
+}
+
+#[test]
+fn test_archive_file_open_error() {
+    assert!(matches!(
+        ArchiveFile::open(fixture().with_file_name("does-not-exist.pgdump")),
+        Err(ArchiveError::OpenError { .. })
+    ));
+}
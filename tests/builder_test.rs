@@ -0,0 +1,52 @@
+mod common;
+
+use common::{build_archive, write_archive, Entry};
+use pgarchive::{Archive, CompressionMethod, DumpId, Offset, Section, TocEntry};
+use std::io::Read;
+
+fn table_data(id: u32, tag: &str, offset: Offset) -> TocEntry {
+    TocEntry {
+        had_dumper: true,
+        namespace: String::from("public"),
+        owner: String::from("test"),
+        offset,
+        ..TocEntry::new(DumpId::new(id).unwrap(), Section::Data, "TABLE DATA", tag)
+    }
+}
+
+#[test]
+fn test_build_archive() -> Result<(), pgarchive::ArchiveError> {
+    let data = build_archive(
+        0,
+        &[Entry::table_data(
+            1,
+            "pizza",
+            vec![b"1\tmargherita\n".to_vec()],
+        )],
+    );
+    let parsed = Archive::parse(&mut &data[..])?;
+    let offset = parsed.toc_entries[0].offset;
+
+    let archive = Archive::builder()
+        .database_name("test")
+        .compression_method(CompressionMethod::None)
+        .toc_entry(table_data(1, "pizza", offset))
+        .toc_entry(table_data(2, "topping", Offset::NoData))
+        .build();
+    assert_eq!(archive.database_name, "test");
+    assert!(archive
+        .find_toc_entry(Section::Data, "TABLE DATA", "crust")
+        .is_none());
+    let entry = archive
+        .find_toc_entry(Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    assert_eq!(entry.id, DumpId::new(1).unwrap());
+
+    let file = write_archive(&data);
+    let mut buffer = Vec::new();
+    archive
+        .read_data(&mut file.reopen()?, entry)?
+        .read_to_end(&mut buffer)?;
+    assert_eq!(buffer, b"1\tmargherita\n");
+    Ok(())
+}
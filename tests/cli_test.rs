@@ -0,0 +1,69 @@
+#![cfg(feature = "cli")]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::io::Write;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join(name)
+        .display()
+        .to_string()
+}
+
+#[test]
+fn test_missing_file() {
+    Command::cargo_bin("pgarchive")
+        .unwrap()
+        .arg("does-not-exist.pgdump")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("does-not-exist.pgdump"));
+}
+
+#[test]
+fn test_corrupt_file() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(b"this is not an archive").unwrap();
+    let path = file.path().display().to_string();
+
+    Command::cargo_bin("pgarchive")
+        .unwrap()
+        .args(["list", &path])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicate::str::contains(path.as_str()))
+        .stderr(predicate::str::contains("PGDMP"));
+}
+
+#[test]
+fn test_continue_after_failure() {
+    Command::cargo_bin("pgarchive")
+        .unwrap()
+        .args(["list", "does-not-exist.pgdump", &fixture("test.pgdump")])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("TABLE DATA public pizza"))
+        .stderr(predicate::str::contains("does-not-exist.pgdump"));
+}
+
+#[test]
+fn test_usage_error() {
+    Command::cargo_bin("pgarchive")
+        .unwrap()
+        .args(["list", "--section", "bogus", &fixture("test.pgdump")])
+        .assert()
+        .code(2)
+        .stdout("")
+        .stderr(predicate::str::contains("valid values are"));
+
+    Command::cargo_bin("pgarchive")
+        .unwrap()
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("usage:"));
+}
@@ -0,0 +1,53 @@
+use pgarchive::{Archive, Comment};
+use std::path::Path;
+
+#[test]
+fn test_comments() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/comments.pgdump");
+    let (archive, _) = Archive::open(path)?;
+    let comments: Vec<(String, String, String)> = archive
+        .comments()
+        .into_iter()
+        .map(|c: Comment| (c.object_type, c.object_name, c.text))
+        .collect();
+    let expected = [
+        ("SCHEMA", "menu", "Menu items"),
+        ("TYPE", "public.size", "Pizza size"),
+        (
+            "FUNCTION",
+            "public.add(a integer, b integer)",
+            "Add numbers",
+        ),
+        ("TABLE", "public.pizza", "All the pizza's"),
+        ("COLUMN", "public.pizza.name", "Name of the pizza"),
+    ];
+    assert_eq!(
+        comments,
+        expected
+            .iter()
+            .map(|&(t, n, c)| (t.to_string(), n.to_string(), c.to_string()))
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_is_global() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/comments.pgdump");
+    let (archive, _) = Archive::open(path)?;
+    let global: Vec<(&str, &str)> = archive
+        .toc_entries
+        .iter()
+        .filter(|e| e.is_global())
+        .map(|e| (e.desc.as_str(), e.tag.as_str()))
+        .collect();
+    assert_eq!(global, [("COMMENT", "SCHEMA menu")]);
+
+    let database = archive
+        .toc_entries
+        .iter()
+        .find(|e| e.desc == "DATABASE")
+        .unwrap();
+    assert!(!database.is_global());
+    Ok(())
+}
@@ -0,0 +1,130 @@
+#![allow(dead_code)]
+//! Helpers to build small synthetic archives for tests.
+
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+pub const INT_SIZE: usize = 4;
+pub const OFFSET_SIZE: usize = 8;
+
+/// A TOC entry to write, including the (already compressed) data chunks.
+pub struct Entry {
+    pub id: i64,
+    pub tag: &'static str,
+    pub desc: &'static str,
+    pub section: i64,
+    pub defn: &'static str,
+    pub copy_stmt: &'static str,
+    pub data: Option<Vec<Vec<u8>>>,
+}
+
+impl Entry {
+    pub fn table_data(id: i64, tag: &'static str, data: Vec<Vec<u8>>) -> Entry {
+        Entry {
+            id,
+            tag,
+            desc: "TABLE DATA",
+            section: 3,
+            defn: "",
+            copy_stmt: "",
+            data: Some(data),
+        }
+    }
+}
+
+pub fn write_int(buf: &mut Vec<u8>, v: i64) {
+    buf.push(if v < 0 { 1 } else { 0 });
+    let v = v.unsigned_abs();
+    for i in 0..INT_SIZE {
+        buf.push((v >> (i * 8)) as u8);
+    }
+}
+
+pub fn write_string(buf: &mut Vec<u8>, s: &str) {
+    write_int(buf, s.len() as i64);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+/// Build a version 1.15 archive using the given compression method byte.
+pub fn build_archive(compression: u8, entries: &[Entry]) -> Vec<u8> {
+    build_archive_version(15, compression, entries)
+}
+
+/// Build an archive with format version 1.`minor`.
+///
+/// Versions from 1.16 use the 1.16 layout, which adds the relkind to TOC
+/// entries.
+pub fn build_archive_version(minor: u8, compression: u8, entries: &[Entry]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(b"PGDMP");
+    buf.extend_from_slice(&[
+        1,
+        minor,
+        0,
+        INT_SIZE as u8,
+        OFFSET_SIZE as u8,
+        1,
+        compression,
+    ]);
+    for v in [20, 53, 7, 24, 10, 122, 0] {
+        write_int(&mut buf, v);
+    }
+    write_string(&mut buf, "test");
+    write_string(&mut buf, "16.0");
+    write_string(&mut buf, "16.0");
+
+    write_int(&mut buf, entries.len() as i64);
+    let mut patches = Vec::new();
+    for entry in entries {
+        write_int(&mut buf, entry.id);
+        write_int(&mut buf, entry.data.is_some() as i64);
+        write_string(&mut buf, "0");
+        write_string(&mut buf, "0");
+        write_string(&mut buf, entry.tag);
+        write_string(&mut buf, entry.desc);
+        write_int(&mut buf, entry.section);
+        write_string(&mut buf, entry.defn);
+        write_string(&mut buf, "");
+        write_string(&mut buf, entry.copy_stmt);
+        write_string(&mut buf, "public");
+        write_string(&mut buf, "");
+        write_string(&mut buf, "");
+        if minor >= 16 {
+            write_int(&mut buf, 'r' as i64);
+        }
+        write_string(&mut buf, "test");
+        write_string(&mut buf, "false");
+        write_int(&mut buf, -1);
+        if entry.data.is_some() {
+            buf.push(2);
+            patches.push(buf.len());
+        } else {
+            buf.push(3);
+        }
+        buf.extend_from_slice(&[0; OFFSET_SIZE]);
+    }
+
+    let mut patches = patches.into_iter();
+    for entry in entries {
+        if let Some(chunks) = &entry.data {
+            let at = patches.next().unwrap();
+            let offset = (buf.len() as u64).to_le_bytes();
+            buf[at..at + OFFSET_SIZE].copy_from_slice(&offset[..OFFSET_SIZE]);
+            buf.push(1);
+            write_int(&mut buf, entry.id);
+            for chunk in chunks {
+                write_int(&mut buf, chunk.len() as i64);
+                buf.extend_from_slice(chunk);
+            }
+            write_int(&mut buf, 0);
+        }
+    }
+    buf
+}
+
+/// Write archive data to a temporary file.
+pub fn write_archive(data: &[u8]) -> NamedTempFile {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(data).unwrap();
+    file
+}
@@ -0,0 +1,21 @@
+//! Regression tests for inputs which used to make the parser panic or abort.
+//!
+//! The files in `tests/corpus/` are minimized inputs found by fuzzing; see
+//! `fuzz/README.md`. Parsing them must fail with an error.
+
+use std::fs;
+use std::path::Path;
+
+#[test]
+fn test_corpus() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+    let mut count = 0;
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        let data = fs::read(&path).unwrap();
+        let result = pgarchive::Archive::parse(&mut &data[..]);
+        assert!(result.is_err(), "{} parsed without error", path.display());
+        count += 1;
+    }
+    assert!(count > 0);
+}
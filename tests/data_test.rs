@@ -1,8 +1,17 @@
 use std::fs::File;
-use std::io::Read;
+#[cfg(feature = "gzip")]
+use std::io::{BufRead, Seek};
+use std::io::{Read, Write};
 use std::path::Path;
+#[cfg(feature = "gzip")]
+use std::sync::Mutex;
+
+mod common;
+
+use common::{build_archive, write_archive, Entry};
 
 #[test]
+#[cfg(feature = "gzip")]
 fn test_table_data() -> Result<(), pgarchive::ArchiveError> {
     let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
     let mut f = File::open(cargo_path.join("test.pgdump"))?;
@@ -10,7 +19,7 @@ fn test_table_data() -> Result<(), pgarchive::ArchiveError> {
     let entry = archive
         .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
         .expect("no data for pizza table present");
-    let mut data = archive.read_data(&mut f, &entry)?;
+    let mut data = archive.read_data(&mut f, entry)?;
     let mut buffer = Vec::new();
     let size = data.read_to_end(&mut buffer)?;
     assert_eq!(size, 66, "expected 66 bytes, but read {}", size);
@@ -20,3 +29,641 @@ fn test_table_data() -> Result<(), pgarchive::ArchiveError> {
     );
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_table_data_from_memory() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let mut cursor = std::io::Cursor::new(std::fs::read(&path)?);
+    let archive = pgarchive::Archive::parse(&mut cursor)?;
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    let mut buffer = String::new();
+    archive
+        .read_data(&mut cursor, entry)?
+        .read_to_string(&mut buffer)?;
+    assert!(buffer.starts_with("1\tThe Classic\n"));
+    assert_eq!(
+        archive.table_data_size(&mut cursor, entry)?,
+        archive.table_data_size(File::open(&path)?, entry)?
+    );
+
+    // The reader can also be passed by value.
+    let mut all = String::new();
+    archive.read_all_data(cursor)?.read_to_string(&mut all)?;
+    assert!(all.contains(&buffer));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_parse_seekable() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let mut f = File::open(&path)?;
+    let archive = pgarchive::Archive::parse_seekable(&mut f)?;
+
+    // The file is left directly after the table of contents, like it is when
+    // parsing without buffering.
+    let mut unbuffered = File::open(&path)?;
+    assert_eq!(archive, pgarchive::Archive::parse(&mut unbuffered)?);
+    assert_eq!(f.stream_position()?, unbuffered.stream_position()?);
+
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    let mut buffer = String::new();
+    archive
+        .read_data(&mut f, entry)?
+        .read_to_string(&mut buffer)?;
+    assert!(buffer.starts_with("1\tThe Classic\n"));
+
+    // Parsing starts at the current position.
+    let mut data = vec![0; 7];
+    data.extend(std::fs::read(&path)?);
+    let mut cursor = std::io::Cursor::new(data);
+    cursor.seek(std::io::SeekFrom::Start(7))?;
+    assert_eq!(pgarchive::Archive::parse_seekable(&mut cursor)?, archive);
+    assert_eq!(cursor.position(), unbuffered.stream_position()? + 7);
+    Ok(())
+}
+
+#[test]
+fn test_entry_reader_reset() -> Result<(), pgarchive::ArchiveError> {
+    let chunks = vec![b"1\tMargherita\n".to_vec(), b"2\tHawaii\n".to_vec()];
+    let data = build_archive(0, &[Entry::table_data(1, "pizza", chunks)]);
+    let mut file = std::io::Cursor::new(data);
+    let archive = pgarchive::Archive::parse(&mut file)?;
+    let entry = &archive.toc_entries[0];
+
+    let mut reader = archive.entry_reader(&mut file, entry)?;
+    let mut first = [0; 4];
+    reader.read_exact(&mut first)?;
+    assert_eq!(&first, b"1\tMa");
+    reader.reset()?;
+    let mut data = String::new();
+    reader.read_to_string(&mut data)?;
+    assert_eq!(data, "1\tMargherita\n2\tHawaii\n");
+    reader.reset()?;
+    let mut again = String::new();
+    reader.read_to_string(&mut again)?;
+    assert_eq!(again, data);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_entry_reader_reset_compressed() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, mut f) = pgarchive::Archive::open(path)?;
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    let mut expected = Vec::new();
+    archive
+        .read_data(&mut f, entry)?
+        .read_to_end(&mut expected)?;
+
+    let mut reader = archive.entry_reader(&mut f, entry)?;
+    let mut first = Vec::new();
+    reader.read_to_end(&mut first)?;
+    reader.reset()?;
+    let mut second = Vec::new();
+    reader.read_to_end(&mut second)?;
+    assert_eq!(first, expected);
+    assert_eq!(second, expected);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_for_each_table() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let archive = pgarchive::Archive::parse(&mut File::open(&path)?)?;
+    let sizes = Mutex::new(Vec::new());
+    archive.for_each_table(&path, |entry, mut data| {
+        let mut buffer = Vec::new();
+        let size = data.read_to_end(&mut buffer).unwrap();
+        sizes.lock().unwrap().push((entry.tag.clone(), size));
+    })?;
+    let mut sizes = sizes.into_inner().unwrap();
+    sizes.sort();
+    assert_eq!(
+        sizes
+            .iter()
+            .map(|(tag, _)| tag.as_str())
+            .collect::<Vec<_>>(),
+        vec!["pizza", "pizza_topping", "topping"]
+    );
+    assert!(sizes.contains(&(String::from("pizza"), 66)));
+    Ok(())
+}
+
+#[test]
+fn test_parse_streaming() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let data = std::fs::read(cargo_path.join("test.pgdump"))?;
+    let mut input = &data[..];
+    pgarchive::Archive::parse(&mut input)?;
+    let after_toc = input.to_vec();
+    let (reader, mut writer) = std::io::pipe()?;
+    let writer = std::thread::spawn(move || writer.write_all(&data));
+
+    let mut reader = std::io::BufReader::new(reader);
+    let archive = pgarchive::Archive::parse_streaming(&mut reader)?;
+    assert_eq!(archive.database_name, "pizza");
+    // Nothing after the table of contents is lost in a buffer.
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest)?;
+    writer.join().unwrap()?;
+    assert_eq!(rest, after_toc);
+
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    let mut f = File::open(cargo_path.join("test.pgdump"))?;
+    match archive.read_data(&mut f, entry) {
+        Err(e @ pgarchive::ArchiveError::NotSeekable) => {
+            assert_eq!(e.to_string(), "data requires a seekable source")
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("reading data from a stream did not fail"),
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_empty_data() -> Result<(), pgarchive::ArchiveError> {
+    let mut methods = vec![0, 1];
+    if cfg!(feature = "zstd") {
+        methods.push(3);
+    }
+    for compression in methods {
+        let no_data = Entry {
+            data: None,
+            ..Entry::table_data(2, "topping", vec![])
+        };
+        let file = write_archive(&build_archive(
+            compression,
+            &[Entry::table_data(1, "pizza", vec![]), no_data],
+        ));
+        let mut f = file.reopen()?;
+        let archive = pgarchive::Archive::parse(&mut f)?;
+        for entry in &archive.toc_entries {
+            let mut buffer = Vec::new();
+            let size = archive.read_data(&mut f, entry)?.read_to_end(&mut buffer)?;
+            assert_eq!(
+                size, 0,
+                "{} data for compression {}",
+                entry.tag, compression
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_restore_sql_for_entry() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let mut f = File::open(path)?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+
+    let data = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .unwrap();
+    assert_eq!(
+        archive.restore_sql_for_entry(&mut f, data)?,
+        "COPY public.pizza (pizza_id, name) FROM stdin;\n1\tThe Classic\n2\tAll Cheese\n3\tVeggie\n4\tThe Everything\n5\tVegan\n\\.\n\n\n"
+    );
+
+    let table = archive
+        .find_toc_entry(pgarchive::Section::PreData, "TABLE", "pizza")
+        .unwrap();
+    assert_eq!(archive.restore_sql_for_entry(&mut f, table)?, table.defn);
+
+    let sequence = archive
+        .toc_entries
+        .iter()
+        .find(|e| e.desc == "SEQUENCE SET")
+        .unwrap();
+    let sql = archive.restore_sql_for_entry(&mut f, sequence)?;
+    assert!(sql.starts_with("SELECT pg_catalog.setval("), "{}", sql);
+    Ok(())
+}
+
+#[test]
+fn test_compression_is_archive_wide() -> Result<(), pgarchive::ArchiveError> {
+    let mut data = build_archive(0, &[Entry::table_data(1, "pizza", vec![b"1\t".to_vec()])]);
+    let archive = pgarchive::Archive::parse(&mut &data[..])?;
+    let offset = archive.toc_entries[0].offset.pos().unwrap() as usize;
+    // A data block starts with the block type and dump id, followed directly
+    // by the first chunk.
+    assert_eq!(data[offset], 1);
+    let chunk = offset + 1 + 1 + common::INT_SIZE;
+    assert_eq!(data[chunk..chunk + 1 + common::INT_SIZE], [0, 2, 0, 0, 0]);
+
+    // Anything else where the block type is expected is rejected.
+    data[offset] = 2;
+    let file = write_archive(&data);
+    let mut f = file.reopen()?;
+    match archive.read_data(&mut f, &archive.toc_entries[0]) {
+        Err(pgarchive::ArchiveError::InvalidData(msg)) => assert_eq!(msg, "invalid block type"),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("reading a block with an invalid type did not fail"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_not_data_entry() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/roles.pgdump");
+    let (archive, mut f) = pgarchive::Archive::open(path)?;
+    let acl = archive
+        .find_toc_entry(pgarchive::Section::None, "ACL", "TABLE topping")
+        .expect("no ACL for topping table present");
+    let table = archive
+        .find_toc_entry(pgarchive::Section::PreData, "TABLE", "topping")
+        .expect("no topping table present");
+    for entry in [acl, table] {
+        match archive.read_data(&mut f, entry) {
+            Err(e @ pgarchive::ArchiveError::NotDataEntry { .. }) => assert_eq!(
+                e.to_string(),
+                format!(
+                    "error reading data for {} public.{} (id {}): entry is not a data section entry",
+                    entry.desc, entry.tag, entry.id
+                )
+            ),
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("reading data for {} did not fail", entry.desc),
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_no_data_present() -> Result<(), pgarchive::ArchiveError> {
+    let mut data = build_archive(
+        0,
+        &[Entry {
+            data: None,
+            ..Entry::table_data(1231, "orders", vec![])
+        }],
+    );
+    // Change the offset flag of the entry from "no data" to "position not set".
+    let flag = data.len() - common::OFFSET_SIZE - 1;
+    assert_eq!(data[flag], 3);
+    data[flag] = 1;
+    let file = write_archive(&data);
+    let mut f = file.reopen()?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    match archive.read_data(&mut f, &archive.toc_entries[0]) {
+        Err(e @ pgarchive::ArchiveError::NoDataPresent { .. }) => assert_eq!(
+            e.to_string(),
+            "error reading data for TABLE DATA public.orders (id 1231): TOC entry has no data"
+        ),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("reading data without a position did not fail"),
+    }
+    Ok(())
+}
+
+#[test]
+fn test_table_for_data_entry() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, _) = pgarchive::Archive::open(path)?;
+    let data = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    let table = archive.table_for_data_entry(data).expect("no table found");
+    assert_eq!(table.desc, "TABLE");
+    assert_eq!(table.tag, "pizza");
+    assert_eq!(archive.table_for_data_entry(table), None);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_copy_lines() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, mut f) = pgarchive::Archive::open(path)?;
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+
+    let lines = archive
+        .read_data_buffered(&mut f, entry)?
+        .lines()
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(lines.len(), 8);
+    assert_eq!(lines[0], "1\tThe Classic");
+
+    let rows = archive
+        .copy_lines(&mut f, entry)?
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(
+        rows,
+        [
+            "1\tThe Classic",
+            "2\tAll Cheese",
+            "3\tVeggie",
+            "4\tThe Everything",
+            "5\tVegan"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_index_entries_for_table() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, _) = pgarchive::Archive::open(path)?;
+    let pizza = archive
+        .find_toc_entry(pgarchive::Section::PreData, "TABLE", "pizza")
+        .expect("no pizza table present");
+    let indexes: Vec<&str> = archive
+        .index_entries_for_table(pizza.id)
+        .map(|e| e.tag.as_str())
+        .collect();
+    let expected: Vec<&str> = archive
+        .indexes()
+        .into_iter()
+        .filter(|i| i.table == "pizza")
+        .map(|i| archive.find_toc_entry_by_id(i.id).unwrap().tag.as_str())
+        .collect();
+    assert!(!indexes.is_empty());
+    assert_eq!(indexes, expected);
+    Ok(())
+}
+
+#[test]
+fn test_constraint_entries_for_table() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, _) = pgarchive::Archive::open(path)?;
+    let constraints = |table: &str| -> Vec<&str> {
+        let table = archive
+            .find_toc_entry(pgarchive::Section::PreData, "TABLE", table)
+            .expect("table not present");
+        archive
+            .constraint_entries_for_table(table.id)
+            .map(|e| e.tag.as_str())
+            .collect()
+    };
+    // Foreign keys are included for the referenced table.
+    assert_eq!(
+        constraints("pizza"),
+        [
+            "pizza pizza_pkey",
+            "pizza_topping pizza_topping_pizza_id_fkey"
+        ]
+    );
+    assert_eq!(
+        constraints("pizza_topping"),
+        [
+            "pizza_topping pizza_topping_pkey",
+            "pizza_topping pizza_topping_pizza_id_fkey",
+            "pizza_topping pizza_topping_topping_id_fkey"
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sequence_entries_for_table() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, _) = pgarchive::Archive::open(path)?;
+    let sequences = |table: &str| -> Vec<&str> {
+        let table = archive
+            .find_toc_entry(pgarchive::Section::PreData, "TABLE", table)
+            .expect("table not present");
+        archive
+            .sequence_entries_for_table(table.id)
+            .map(|e| e.tag.as_str())
+            .collect()
+    };
+    assert_eq!(sequences("pizza"), ["pizza_pizza_id_seq"]);
+    assert_eq!(sequences("topping"), ["topping_topping_id_seq"]);
+    assert!(sequences("pizza_topping").is_empty());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_with_data() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, mut f) = pgarchive::Archive::open(path)?;
+    for entry in archive
+        .toc_entries
+        .iter()
+        .filter(|e| e.desc == "TABLE DATA")
+    {
+        let mut expected = Vec::new();
+        archive
+            .read_data(&mut f, entry)?
+            .read_to_end(&mut expected)?;
+        let mut buffer = Vec::new();
+        archive.with_data(&mut f, entry, |data| data.read_to_end(&mut buffer))??;
+        assert_eq!(buffer, expected);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_read_all_data() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, mut f) = pgarchive::Archive::open(path)?;
+    let mut expected = Vec::new();
+    for entry in archive
+        .toc_entries
+        .iter()
+        .filter(|e| e.desc == "TABLE DATA")
+    {
+        writeln!(
+            expected,
+            "-- Data for Name: {}; Type: TABLE DATA; Schema: {}",
+            entry.tag, entry.namespace
+        )?;
+        archive
+            .read_data(&mut f, entry)?
+            .read_to_end(&mut expected)?;
+    }
+
+    let mut data = Vec::new();
+    archive.read_all_data(&mut f)?.read_to_end(&mut data)?;
+    assert_eq!(data, expected);
+    let data = String::from_utf8(data).unwrap();
+    assert!(data.starts_with("-- Data for Name: "));
+    assert!(data
+        .contains("-- Data for Name: pizza; Type: TABLE DATA; Schema: public\n1\tThe Classic\n"));
+
+    // Entries without data are skipped.
+    let file = write_archive(&build_archive(
+        0,
+        &[
+            Entry {
+                data: None,
+                ..Entry::table_data(1, "empty", vec![])
+            },
+            Entry::table_data(2, "pizza", vec![b"1\tmargherita\n".to_vec()]),
+        ],
+    ));
+    let mut f = file.reopen()?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    let mut data = String::new();
+    archive.read_all_data(&mut f)?.read_to_string(&mut data)?;
+    assert_eq!(
+        data,
+        "-- Data for Name: pizza; Type: TABLE DATA; Schema: public\n1\tmargherita\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_truncated_data_error() -> Result<(), pgarchive::ArchiveError> {
+    let mut data = build_archive(
+        0,
+        &[Entry::table_data(
+            1231,
+            "orders",
+            vec![b"1\tmargherita\n".to_vec()],
+        )],
+    );
+    // Cut the file in the middle of the data chunk.
+    data.truncate(data.len() - 10);
+    let file = write_archive(&data);
+    let mut f = file.reopen()?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "orders")
+        .expect("no data for orders table present");
+
+    let mut buffer = Vec::new();
+    let result = archive.read_data(&mut f, entry)?.read_to_end(&mut buffer);
+    let error = pgarchive::ArchiveError::from(result.unwrap_err());
+    assert!(matches!(
+        error,
+        pgarchive::ArchiveError::ReadDataError { id, .. } if id == pgarchive::DumpId::new(1231).unwrap()
+    ));
+    assert_eq!(
+        error.to_string(),
+        "error reading data for TABLE DATA public.orders (id 1231): unexpected end of file"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_table_data_size() -> Result<(), pgarchive::ArchiveError> {
+    let file = write_archive(&build_archive(
+        0,
+        &[
+            Entry {
+                data: None,
+                ..Entry::table_data(1, "empty", vec![])
+            },
+            Entry::table_data(2, "pizza", vec![vec![b'x'; 10], vec![b'y'; 5]]),
+        ],
+    ));
+    let mut f = file.reopen()?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    let sizes: Vec<u64> = archive
+        .toc_entries
+        .iter()
+        .map(|e| archive.table_data_size(&mut f, e))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(sizes, [0, 15]);
+    assert_eq!(
+        archive.estimate_restore_time(&mut f, std::num::NonZeroU64::new(5).unwrap())?,
+        std::time::Duration::from_secs(3)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_missing_terminator() -> Result<(), pgarchive::ArchiveError> {
+    let mut data = build_archive(
+        0,
+        &[
+            Entry::table_data(1, "pizza", vec![b"pizza".to_vec()]),
+            Entry::table_data(2, "pasta", vec![b"pasta".to_vec()]),
+        ],
+    );
+    let archive = pgarchive::Archive::parse(&mut &data[..])?;
+    let pgarchive::Offset::PosSet(next) = archive.toc_entries[1].offset else {
+        panic!("no offset for pasta data");
+    };
+    // Replace the zero length ending the pizza data with the length of a
+    // chunk which runs into the pasta data.
+    let next = next as usize;
+    data[next - 5..next].copy_from_slice(&[0, 3, 0, 0, 0]);
+    let file = write_archive(&data);
+    let mut f = file.reopen()?;
+
+    let err = archive
+        .read_data(&mut f, &archive.toc_entries[0])?
+        .read_to_end(&mut Vec::new())
+        .unwrap_err();
+    match pgarchive::ArchiveError::from(err) {
+        pgarchive::ArchiveError::ReadDataError { id, source, .. } => {
+            assert_eq!(id, archive.toc_entries[0].id);
+            assert_eq!(source.kind(), std::io::ErrorKind::InvalidData);
+            assert_eq!(
+                source.to_string(),
+                "data does not end before the next data block"
+            );
+        }
+        e => panic!("unexpected error: {}", e),
+    }
+    assert!(archive
+        .table_data_size(&mut f, &archive.toc_entries[0])
+        .is_err());
+
+    // The next entry is not affected.
+    let mut buffer = Vec::new();
+    archive
+        .read_data(&mut f, &archive.toc_entries[1])?
+        .read_to_end(&mut buffer)?;
+    assert_eq!(buffer, b"pasta");
+    Ok(())
+}
+
+#[test]
+fn test_copy_table_data_raw() -> Result<(), pgarchive::ArchiveError> {
+    let chunks = vec![b"pizza".to_vec(), b"pasta".to_vec()];
+    let file = write_archive(&build_archive(
+        0,
+        &[
+            Entry {
+                data: None,
+                ..Entry::table_data(1, "empty", vec![])
+            },
+            Entry::table_data(2, "pizza", chunks.clone()),
+        ],
+    ));
+    let mut f = file.reopen()?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+
+    let mut output = Vec::new();
+    let entry = &archive.toc_entries[0];
+    assert_eq!(archive.copy_table_data_raw(&mut f, entry, &mut output)?, 0);
+    assert!(output.is_empty());
+
+    let entry = &archive.toc_entries[1];
+    let size = archive.copy_table_data_raw(&mut f, entry, &mut output)?;
+    let mut expected = Vec::new();
+    for chunk in &chunks {
+        common::write_int(&mut expected, chunk.len() as i64);
+        expected.extend_from_slice(chunk);
+    }
+    common::write_int(&mut expected, 0);
+    assert_eq!(output, expected);
+    assert_eq!(size, expected.len() as u64);
+
+    // The copy is a valid sequence of data chunks.
+    let mut data = Vec::new();
+    pgarchive::DataReader::new(&output[..], 4).read_to_end(&mut data)?;
+    assert_eq!(data, b"pizzapasta");
+    Ok(())
+}
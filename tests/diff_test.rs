@@ -0,0 +1,88 @@
+use pgarchive::{Archive, DumpId, Section};
+use std::fs::File;
+use std::path::Path;
+
+fn load() -> Archive {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    Archive::parse(&mut File::open(path).unwrap()).unwrap()
+}
+
+#[test]
+fn test_diff_identical() {
+    let archive = load();
+    let mut other = load();
+    other.toc_entries.reverse();
+    assert!(archive.diff(&other).is_empty());
+}
+
+#[test]
+fn test_diff_modified() {
+    let archive = load();
+    let mut other = load();
+
+    let index = other
+        .toc_entries
+        .iter()
+        .position(|e| e.desc == "INDEX")
+        .unwrap();
+    let removed = other.toc_entries.remove(index);
+
+    let table = other
+        .toc_entries
+        .iter_mut()
+        .find(|e| e.desc == "TABLE" && e.tag == "pizza")
+        .unwrap();
+    table.defn = table.defn.replace("name text", "name character varying");
+
+    let mut added = other
+        .find_toc_entry(Section::PreData, "TABLE", "topping")
+        .unwrap()
+        .clone_with_new_id(DumpId::new(9999).unwrap());
+    added.tag = String::from("crust");
+    other.toc_entries.push(added);
+
+    let diff = archive.diff(&other);
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0], &removed);
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].tag, "crust");
+    assert_eq!(diff.changed.len(), 1);
+    let (old, new) = diff.changed[0];
+    assert_eq!(old.tag, "pizza");
+    assert_ne!(old.defn, new.defn);
+
+    // The reverse comparison swaps added and removed entries.
+    let reverse = other.diff(&archive);
+    assert_eq!(reverse.added[0], &removed);
+    assert_eq!(reverse.removed[0].tag, "crust");
+}
+
+#[test]
+fn test_schema_diff_sql() -> Result<(), pgarchive::ArchiveError> {
+    let archive = load();
+    assert_eq!(Archive::schema_diff_sql(&archive, &load())?, "");
+
+    let mut other = load();
+    other
+        .toc_entries
+        .retain(|e| !(e.desc == "TABLE" && e.tag == "topping"));
+    let table = other
+        .toc_entries
+        .iter_mut()
+        .find(|e| e.desc == "TABLE" && e.tag == "pizza")
+        .unwrap();
+    table.defn = table.defn.replace("name text", "name character varying");
+    let mut added = table.clone_with_new_id(DumpId::new(9999).unwrap());
+    added.tag = String::from("crust");
+    added.defn = String::from("CREATE TABLE public.crust (\n    name text\n);\n");
+    other.toc_entries.push(added);
+
+    let sql = Archive::schema_diff_sql(&archive, &other)?;
+    assert_eq!(
+        sql,
+        "DROP TABLE IF EXISTS \"public\".\"topping\";\n\
+         CREATE TABLE public.crust (\n    name text\n);\n\
+         -- CHANGED: schema diff not supported; manual review required (table \"public\".\"pizza\")\n"
+    );
+    Ok(())
+}
@@ -0,0 +1,334 @@
+mod common;
+
+use common::{build_archive, build_archive_version, write_archive, Entry};
+use pgarchive::{Archive, ArchiveError, CompressionMethod, ParseOptions, WarningKind};
+use std::fs;
+use std::path::Path;
+
+fn fixture() -> Vec<u8> {
+    fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump")).unwrap()
+}
+
+#[test]
+fn test_truncated_header() {
+    let data = fixture();
+    // Magic, version, integer size and offset size are present, but the
+    // format byte is missing.
+    match Archive::parse(&mut &data[..10]) {
+        Err(ArchiveError::ParseError {
+            offset,
+            context,
+            source,
+        }) => {
+            assert_eq!(offset, 10);
+            assert_eq!(context, "Archive.format");
+            assert!(matches!(*source, ArchiveError::IOError(_)));
+        }
+        r => panic!("unexpected result: {:?}", r),
+    }
+}
+
+#[test]
+fn test_truncated_toc() {
+    let data = fixture();
+    // The table of contents ends well after the first 4000 bytes.
+    match Archive::parse(&mut &data[..4000]) {
+        Err(e @ ArchiveError::ParseError { .. }) => {
+            let message = e.to_string();
+            assert!(message.contains("of TOC entry"), "{}", message);
+            assert!(message.contains("at offset"), "{}", message);
+            let ArchiveError::ParseError { offset, source, .. } = e else {
+                unreachable!()
+            };
+            assert!(offset < 4000);
+            assert!(matches!(*source, ArchiveError::TruncatedData { .. }));
+        }
+        r => panic!("unexpected result: {:?}", r),
+    }
+}
+
+#[test]
+fn test_corrupt_section() {
+    let mut data = build_archive(
+        0,
+        &[
+            Entry::table_data(1, "pizza", vec![]),
+            Entry::table_data(2, "topping", vec![]),
+        ],
+    );
+    // The section follows the length-prefixed description of the second entry.
+    let desc = data
+        .windows(10)
+        .enumerate()
+        .filter(|(_, w)| w == b"TABLE DATA")
+        .map(|(i, _)| i)
+        .nth(1)
+        .unwrap();
+    let section = desc + 10;
+    data[section + 1] = 42;
+
+    match Archive::parse(&mut &data[..]) {
+        Err(ArchiveError::ParseError {
+            offset,
+            context,
+            source,
+        }) => {
+            assert_eq!(offset, section as u64);
+            assert_eq!(context, "TocEntry.section of TOC entry 1 (id 2)");
+            assert!(matches!(*source, ArchiveError::InvalidSection(42)));
+        }
+        r => panic!("unexpected result: {:?}", r),
+    }
+}
+
+fn metadata_entry() -> Entry {
+    Entry {
+        id: 1,
+        tag: "pizza",
+        desc: "TABLE",
+        section: 2,
+        defn: "",
+        copy_stmt: "",
+        data: None,
+    }
+}
+
+#[test]
+fn test_bad_magic() {
+    let e = Archive::parse(&mut &b"PGDMX\x01\x0f\x00"[..]).unwrap_err();
+    assert!(matches!(e.root_cause(), ArchiveError::BadMagic));
+}
+
+#[test]
+fn test_unknown_compression() -> Result<(), ArchiveError> {
+    let file = write_archive(&build_archive(
+        9,
+        &[Entry::table_data(1, "pizza", vec![b"data".to_vec()])],
+    ));
+    let mut f = file.reopen()?;
+    let archive = Archive::parse(&mut f)?;
+    assert_eq!(archive.compression_method, CompressionMethod::Unknown(9));
+    assert_eq!(archive.toc_entries.len(), 1);
+    assert!(matches!(
+        archive.read_data(&mut f, &archive.toc_entries[0]),
+        Err(ArchiveError::CompressionMethodNotSupported(
+            CompressionMethod::Unknown(9)
+        ))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_string_not_utf8() {
+    let mut data = build_archive(0, &[metadata_entry()]);
+    let tag = data.windows(5).position(|w| w == b"pizza").unwrap();
+    data[tag] = 0xff;
+    let e = Archive::parse(&mut &data[..]).unwrap_err();
+    if let ArchiveError::StringNotUtf8 { field, source } = e.root_cause() {
+        assert_eq!(*field, Some("TocEntry.tag"));
+        assert_eq!(source.utf8_error().valid_up_to(), 0);
+        let cause = std::error::Error::source(e.root_cause()).unwrap();
+        assert!(cause.is::<std::string::FromUtf8Error>());
+    } else {
+        panic!("unexpected error: {:?}", e);
+    }
+}
+
+#[test]
+fn test_invalid_offset_flag() {
+    let mut data = build_archive(0, &[metadata_entry()]);
+    // The TOC ends with the offset flag and the offset of the only entry.
+    let flag = data.len() - common::OFFSET_SIZE - 1;
+    data[flag] = 7;
+    let e = Archive::parse(&mut &data[..]).unwrap_err();
+    assert!(matches!(e.root_cause(), ArchiveError::InvalidOffsetFlag(7)));
+}
+
+#[test]
+fn test_max_string_length() {
+    let defn: &'static str = "x".repeat(10 * 1024 * 1024).leak();
+    let data = build_archive(
+        0,
+        &[Entry {
+            defn,
+            ..metadata_entry()
+        }],
+    );
+
+    let archive = Archive::parse(&mut &data[..]).unwrap();
+    assert_eq!(archive.toc_entries[0].defn.len(), 10 * 1024 * 1024);
+
+    let options = pgarchive::ParseOptions::new().max_string_length(1024 * 1024);
+    let e = Archive::parse_with_options(&mut &data[..], &options).unwrap_err();
+    assert!(e.to_string().contains("TocEntry.defn"), "{}", e);
+    assert!(matches!(
+        e.root_cause(),
+        ArchiveError::StringTooLong {
+            length: 10485760,
+            max: 1048576
+        }
+    ));
+}
+
+#[test]
+fn test_newer_version() -> Result<(), ArchiveError> {
+    let mut data = build_archive_version(17, 0, &[metadata_entry()]);
+
+    let e = Archive::parse(&mut &data[..]).unwrap_err();
+    assert!(matches!(
+        e.root_cause(),
+        ArchiveError::UnsupportedVersionError(v) if *v == (1, 17, 0)
+    ));
+
+    let options = ParseOptions::new().lenient(true);
+    let archive = Archive::parse_with_options(&mut &data[..], &options)?;
+    assert_eq!(archive.version, (1, 17, 0));
+    assert_eq!(archive.toc_entries.len(), 1);
+    assert_eq!(archive.warnings().len(), 1);
+    assert_eq!(archive.warnings()[0].kind, WarningKind::UnsupportedVersion);
+    assert_eq!(archive.warnings()[0].offset, 5);
+
+    // Lenient parsing still fails if the data does not match.
+    let mut corrupt = data.clone();
+    let flag = corrupt.len() - common::OFFSET_SIZE - 1;
+    corrupt[flag] = 7;
+    assert!(Archive::parse_with_options(&mut &corrupt[..], &options).is_err());
+
+    // Other major versions are never accepted. The major version directly
+    // follows the magic.
+    data[5] = 2;
+    assert!(Archive::parse_with_options(&mut &data[..], &options).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_parse_header_best_effort() -> Result<(), ArchiveError> {
+    let data = build_archive_version(99, 0, &[metadata_entry()]);
+    assert!(matches!(
+        Archive::parse_header(&mut &data[..]).unwrap_err().root_cause(),
+        ArchiveError::UnsupportedVersionError(v) if *v == (1, 99, 0)
+    ));
+
+    let mut input = &data[..];
+    let header = Archive::parse_header_best_effort(&mut input)?;
+    assert_eq!(header.version, (1, 99, 0));
+    assert_eq!(header.database_name, "test");
+    assert_eq!(header.server_version, "16.0");
+    assert_eq!(header.pgdump_version, "16.0");
+    assert_eq!(header.raw_create_date.to_string(), "2022-11-24T07:53:20");
+    assert_eq!(header.warnings().len(), 1);
+    assert_eq!(header.warnings()[0].kind, WarningKind::UnsupportedVersion);
+    assert_eq!(
+        header.warnings()[0].message,
+        "archive format 1.99.0 is not supported, TOC skipped"
+    );
+
+    // The table of contents is only read from a newer format when lenient.
+    assert!(matches!(
+        Archive::parse_toc(header.clone(), &mut &input[..]),
+        Err(ArchiveError::UnsupportedVersionError(v)) if v == (1, 99, 0)
+    ));
+    let options = ParseOptions::new().lenient(true);
+    let archive = Archive::parse_toc_with_options(header, &mut &input[..], &options)?;
+    assert_eq!(archive.len(), 1);
+
+    // Supported formats are read without warnings.
+    let data = build_archive(0, &[metadata_entry()]);
+    let header = Archive::parse_header_best_effort(&mut &data[..])?;
+    assert_eq!(header, Archive::parse_header(&mut &data[..])?);
+    assert!(header.warnings().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_warnings() -> Result<(), ArchiveError> {
+    let data = build_archive(0, &[metadata_entry()]);
+    assert!(Archive::parse(&mut &data[..])?.warnings().is_empty());
+
+    // The daylight saving time flag is the last of the seven date fields
+    // following the 12 byte start of the header.
+    let mut corrupt = data.clone();
+    corrupt[12 + 6 * (common::INT_SIZE + 1) + 1] = 7;
+    // Replace the database name with an empty string.
+    let name = 12 + 7 * (common::INT_SIZE + 1);
+    let mut empty = Vec::new();
+    common::write_string(&mut empty, "");
+    corrupt.splice(name..name + common::INT_SIZE + 1 + 4, empty);
+    // Add a dependency on id 0 before the end of the dependency list.
+    let mut end = b"false".to_vec();
+    common::write_int(&mut end, -1);
+    let deps = corrupt.windows(end.len()).position(|w| w == end).unwrap() + b"false".len();
+    let mut dep = Vec::new();
+    common::write_string(&mut dep, "0");
+    corrupt.splice(deps..deps, dep);
+
+    let archive = Archive::parse(&mut &corrupt[..])?;
+    assert_eq!(archive.database_name, "");
+    assert!(archive.toc_entries[0].dependencies.is_empty());
+    let kinds: Vec<WarningKind> = archive.warnings().iter().map(|w| w.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            WarningKind::InvalidDst,
+            WarningKind::EmptyDatabaseName,
+            WarningKind::InvalidDependency
+        ]
+    );
+    assert_eq!(archive.warnings()[0].offset, 42);
+    assert_eq!(archive.warnings()[1].offset, name as u64);
+    assert_eq!(archive.warnings()[2].offset, deps as u64);
+    Ok(())
+}
+
+/// Replace one of the seven date fields in the header of a synthetic archive.
+fn set_date_field(data: &mut [u8], index: usize, value: i64) {
+    let mut field = Vec::new();
+    common::write_int(&mut field, value);
+    let start = 12 + index * (common::INT_SIZE + 1);
+    data[start..start + field.len()].copy_from_slice(&field);
+}
+
+#[test]
+fn test_invalid_month() {
+    let mut data = build_archive(0, &[metadata_entry()]);
+    set_date_field(&mut data, 4, 200);
+    let err = Archive::parse(&mut &data[..]).unwrap_err();
+    assert!(matches!(
+        err.root_cause(),
+        ArchiveError::InvalidCreateDate {
+            field: "month",
+            mon: 200,
+            year: 122,
+            ..
+        }
+    ));
+    assert_eq!(
+        err.root_cause().to_string(),
+        "invalid month in creation date (sec=20 min=53 hour=7 mday=24 mon=200 year=122)"
+    );
+}
+
+#[test]
+fn test_invalid_hour_lenient() -> Result<(), ArchiveError> {
+    let mut data = build_archive(0, &[metadata_entry()]);
+    set_date_field(&mut data, 2, 25);
+    assert!(matches!(
+        Archive::parse(&mut &data[..]).unwrap_err().root_cause(),
+        ArchiveError::InvalidCreateDate {
+            field: "hour",
+            hour: 25,
+            ..
+        }
+    ));
+
+    let archive = Archive::parse_with_options(&mut &data[..], &ParseOptions::new().lenient(true))?;
+    // The raw fields are kept, but the date falls back to the epoch.
+    assert_eq!(archive.raw_create_date.hour, 25);
+    #[cfg(feature = "chrono")]
+    assert_eq!(archive.create_date(), chrono::NaiveDateTime::default());
+    assert_eq!(archive.toc_entries.len(), 1);
+    let warning = &archive.warnings()[0];
+    assert_eq!(warning.kind, WarningKind::InvalidCreateDate);
+    assert!(warning.message.contains("hour=25"), "{}", warning.message);
+    Ok(())
+}
@@ -1,4 +1,6 @@
 use std::fs::File;
+#[cfg(feature = "gzip")]
+use std::io::Read;
 use std::path::Path;
 
 #[test]
@@ -9,7 +11,7 @@ fn test_parse_header() -> Result<(), pgarchive::ArchiveError> {
     assert_eq!(archive.database_name, "pizza");
     assert_eq!(
         archive.compression_method,
-        pgarchive::CompressionMethod::ZSTD
+        pgarchive::CompressionMethod::Gzip(-1)
     );
     assert_eq!(
         archive
@@ -20,6 +22,169 @@ fn test_parse_header() -> Result<(), pgarchive::ArchiveError> {
             .collect::<Vec<String>>(),
         vec!["pizza", "pizza_topping", "topping"]
     );
+    assert_eq!(archive.raw_create_date.to_string(), "2023-02-03T16:52:37");
+    #[cfg(feature = "chrono")]
+    assert_eq!(
+        archive.created_at_local().to_string(),
+        "2023-02-03 16:52:37"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_trait_object() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut file = File::open(cargo_path.join("test.pgdump"))?;
+    let f: &mut dyn std::io::Read = &mut file;
+    let archive = pgarchive::Archive::parse(f)?;
+    assert_eq!(archive.database_name, "pizza");
+    Ok(())
+}
+
+#[test]
+fn test_indexes() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut f = File::open(cargo_path.join("test.pgdump"))?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    let indexes = archive.indexes();
+    assert_eq!(indexes.len(), 1);
+    assert_eq!(indexes[0].name, "pizza_name");
+    assert_eq!(indexes[0].namespace, "public");
+    assert_eq!(indexes[0].table, "pizza");
+    assert!(!indexes[0].unique);
+    Ok(())
+}
+
+#[test]
+fn test_archive_file_size() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let f = File::open(cargo_path.join("test.pgdump"))?;
+    assert_eq!(pgarchive::Archive::archive_file_size(&f)?, 5813);
+    Ok(())
+}
+
+#[test]
+fn test_parse_with_limit() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut f = File::open(cargo_path.join("test.pgdump"))?;
+    let archive = pgarchive::Archive::parse_with_limit(&mut f, 1)?;
+    assert_eq!(archive.toc_entries.len(), 1);
+    assert_eq!(archive.toc_entries[0].desc, "ENCODING");
+    Ok(())
+}
+
+#[test]
+fn test_display() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut f = File::open(cargo_path.join("test.pgdump"))?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    assert_eq!(
+        format!("{}", archive),
+        "version=1.14.0 compression=Gzip(-1) database=\"pizza\" created=2023-02-03T16:52:37 \
+         server_version=\"14.6 (Homebrew)\" toc_entries=20 data_entries=5"
+    );
+    assert_eq!(
+        format!("{:#}", archive),
+        "version: 1.14.0\n\
+         compression: Gzip(-1)\n\
+         database: pizza\n\
+         created: 2023-02-03T16:52:37\n\
+         server_version: 14.6 (Homebrew)\n\
+         toc_entries: 20\n\
+         data_entries: 5"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_header_only() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let archive = pgarchive::Archive::parse(&mut File::open(&path)?)?;
+
+    let mut f = File::open(&path)?;
+    let header = pgarchive::Archive::parse_header(&mut f)?;
+    assert_eq!(header.version, archive.version);
+    assert_eq!(header.compression_method, archive.compression_method);
+    assert_eq!(header.raw_create_date, archive.raw_create_date);
+    assert_eq!(header.database_name, "pizza");
+    assert_eq!(header.server_version, archive.server_version);
+    assert_eq!(header.pgdump_version, archive.pgdump_version);
+
+    // The reader is left at the table of contents.
+    let rest = pgarchive::Archive::parse_toc(header, &mut f)?;
+    assert_eq!(rest, archive);
+    Ok(())
+}
+
+#[test]
+fn test_parse_header_with_options() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let options = pgarchive::ParseOptions::new().max_string_length(3);
+    assert!(matches!(
+        pgarchive::Archive::parse_header_with_options(&mut File::open(&path)?, &options)
+            .unwrap_err()
+            .root_cause(),
+        pgarchive::ArchiveError::StringTooLong { max: 3, .. }
+    ));
 
+    let mut f = File::open(&path)?;
+    let header = pgarchive::Archive::parse_header(&mut f)?;
+    let options = pgarchive::ParseOptions::new().max_entries(2);
+    let archive = pgarchive::Archive::parse_toc_with_options(header, &mut f, &options)?;
+    assert_eq!(archive.len(), 2);
     Ok(())
 }
+
+#[test]
+fn test_parse_header_stream() -> Result<(), pgarchive::ArchiveError> {
+    let data = std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump"))?;
+    let mut input = &data[..];
+    pgarchive::Archive::parse_header(&mut input)?;
+    let header_size = data.len() - input.len();
+
+    // A stream which ends directly after the header.
+    let mut input = &data[..header_size];
+    let header = pgarchive::Archive::parse_header(&mut input)?;
+    assert_eq!(header.database_name, "pizza");
+    assert!(input.is_empty());
+    assert!(pgarchive::Archive::parse(&mut &data[..header_size]).is_err());
+    assert!(pgarchive::Archive::parse_toc(header, &mut input).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_inferred_dump_options() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let archive = pgarchive::Archive::parse(&mut File::open(path)?)?;
+    assert!(!archive.was_data_only());
+    assert!(!archive.was_schema_only());
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_open() -> Result<(), pgarchive::ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, mut file) = pgarchive::Archive::open(&path)?;
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .unwrap();
+    let mut buffer = Vec::new();
+    archive
+        .read_data(&mut file, entry)?
+        .read_to_end(&mut buffer)?;
+    assert!(!buffer.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_open_missing_file() {
+    match pgarchive::Archive::open("does-not-exist.pgdump") {
+        Err(e @ pgarchive::ArchiveError::OpenError { .. }) => {
+            assert_eq!(e.to_string(), "can not open does-not-exist.pgdump");
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("opening a missing file did not fail"),
+    }
+}
@@ -0,0 +1,73 @@
+use pgarchive::{Archive, ArchiveError, ParseOptions, Section};
+use std::cell::Cell;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::rc::Rc;
+
+/// Reader which counts the number of bytes read.
+struct CountingReader<R> {
+    inner: R,
+    count: Rc<Cell<usize>>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.set(self.count.get() + n);
+        Ok(n)
+    }
+}
+
+fn fixture() -> Vec<u8> {
+    std::fs::read(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump")).unwrap()
+}
+
+#[test]
+fn test_lazy_toc() -> Result<(), ArchiveError> {
+    let data = fixture();
+    let mut input = &data[..];
+    Archive::parse_header(&mut input)?;
+    let header_size = data.len() - input.len();
+
+    let count = Rc::new(Cell::new(0));
+    let reader = CountingReader {
+        inner: &data[..],
+        count: count.clone(),
+    };
+    let mut archive = Archive::parse_lazy(reader)?;
+    assert_eq!(archive.header().database_name, "pizza");
+    assert_eq!(
+        count.get(),
+        header_size,
+        "TOC was read before it was needed"
+    );
+
+    assert_eq!(archive.toc_entries()?.len(), 20);
+    let after_toc = count.get();
+    assert!(after_toc > header_size);
+
+    // The table of contents is only read once.
+    let entry = archive.find_toc_entry(Section::Data, "TABLE DATA", "pizza")?;
+    assert!(entry.is_some());
+    assert_eq!(count.get(), after_toc);
+    Ok(())
+}
+
+#[test]
+fn test_lazy_into_archive() -> Result<(), ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let archive = Archive::parse(&mut File::open(&path)?)?;
+    let lazy = Archive::parse_lazy(File::open(&path)?)?;
+    assert_eq!(lazy.into_archive()?, archive);
+    Ok(())
+}
+
+#[test]
+fn test_lazy_with_options() -> Result<(), ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let options = ParseOptions::new().max_entries(3);
+    let mut lazy = Archive::parse_lazy_with_options(File::open(&path)?, &options)?;
+    assert_eq!(lazy.toc_entries()?.len(), 3);
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use pgarchive::{Archive, ArchiveError, Offset, RoleUsage, Section, TocEntry};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+#[test]
+fn test_roles() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut f = File::open(cargo_path.join("roles.pgdump"))?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    assert_eq!(
+        archive.roles(),
+        vec![
+            RoleUsage {
+                name: String::from("alice"),
+                owned_objects: 2,
+                granted_privileges: 2,
+            },
+            RoleUsage {
+                name: String::from("bob smith"),
+                owned_objects: 0,
+                granted_privileges: 1,
+            },
+            RoleUsage {
+                name: String::from("wichert"),
+                owned_objects: 3,
+                granted_privileges: 0,
+            },
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_acl_grants() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut f = File::open(cargo_path.join("roles.pgdump"))?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    let acl = archive
+        .find_toc_entry(pgarchive::Section::None, "ACL", "TABLE topping")
+        .expect("no ACL for topping table present");
+    let grants = acl.acl_grants();
+    assert_eq!(grants.len(), 1);
+    assert_eq!(grants[0].grantee, "PUBLIC");
+    assert_eq!(grants[0].object, "TABLE public.topping");
+    Ok(())
+}
+
+/// Return the data of all entries with data, as stored in the archive.
+fn table_data(archive: &Archive, f: &mut (impl Read + Seek)) -> Result<Vec<Vec<u8>>, ArchiveError> {
+    let mut data = Vec::new();
+    for entry in archive.toc_entries.iter().filter(|e| e.had_dumper) {
+        let mut buffer = Vec::new();
+        archive.copy_table_data_raw(&mut *f, entry, &mut buffer)?;
+        data.push(buffer);
+    }
+    Ok(data)
+}
+
+/// Return the TOC entries without their data offsets, which differ between
+/// written archives.
+fn entries_without_offsets(archive: &Archive) -> Vec<TocEntry> {
+    archive
+        .toc_entries
+        .iter()
+        .map(|e| TocEntry {
+            offset: Offset::Unknown,
+            ..e.clone()
+        })
+        .collect()
+}
+
+#[test]
+fn test_map_owners() -> Result<(), ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let (original, mut f) = Archive::open(cargo_path.join("roles.pgdump"))?;
+    let mapping = HashMap::from([
+        (String::from("wichert"), String::from("postgres")),
+        (String::from("bob smith"), String::from("bob")),
+    ]);
+    let mut out = Vec::new();
+    original.map_owners(&mut f, &mut out, &mapping)?;
+
+    let mut written = Cursor::new(out);
+    let archive = Archive::parse(&mut written)?;
+    assert_eq!(archive.database_name, original.database_name);
+    assert_eq!(
+        archive
+            .roles()
+            .into_iter()
+            .map(|r| (r.name, r.owned_objects, r.granted_privileges))
+            .collect::<Vec<_>>(),
+        vec![
+            (String::from("alice"), 2, 2),
+            (String::from("bob"), 0, 1),
+            (String::from("postgres"), 3, 0),
+        ]
+    );
+    let acl = archive
+        .find_toc_entry(Section::None, "ACL", "TABLE pizza")
+        .expect("no ACL for pizza table present");
+    assert!(acl
+        .defn
+        .contains("GRANT SELECT ON TABLE public.pizza TO bob WITH GRANT OPTION;"));
+    assert_eq!(
+        table_data(&archive, &mut written)?,
+        table_data(&original, &mut f)?
+    );
+
+    // Mapping the names back restores the original archive.
+    let reverse = mapping
+        .iter()
+        .map(|(k, v)| (v.clone(), k.clone()))
+        .collect();
+    let mut out = Vec::new();
+    archive.map_owners(&mut written, &mut out, &reverse)?;
+    let mut restored = Cursor::new(out);
+    let archive = Archive::parse(&mut restored)?;
+    assert_eq!(
+        entries_without_offsets(&archive),
+        entries_without_offsets(&original)
+    );
+    assert_eq!(
+        table_data(&archive, &mut restored)?,
+        table_data(&original, &mut f)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_map_owners_database_and_default_acl() -> Result<(), ArchiveError> {
+    let entry = |desc: &str, owner: &str, defn: &str| pgarchive::TocEntry {
+        owner: owner.into(),
+        defn: defn.into(),
+        ..pgarchive::TocEntry::new(
+            pgarchive::DumpId::new(1).unwrap(),
+            pgarchive::Section::None,
+            desc,
+            "pizza",
+        )
+    };
+    let archive = pgarchive::Archive::builder()
+        .toc_entry(entry(
+            "DATABASE",
+            "wichert",
+            "CREATE DATABASE pizza WITH TEMPLATE = template0;\n",
+        ))
+        .toc_entry(entry(
+            "ACL",
+            "wichert",
+            "REVOKE CONNECT,TEMPORARY ON DATABASE pizza FROM PUBLIC;\n\
+             GRANT CONNECT ON DATABASE pizza TO wichert;\n",
+        ))
+        .toc_entry(entry(
+            "DEFAULT ACL",
+            "wichert",
+            "ALTER DEFAULT PRIVILEGES FOR ROLE wichert IN SCHEMA public GRANT SELECT ON TABLES TO wichert;\n",
+        ))
+        .toc_entry(entry(
+            "TABLE",
+            "",
+            "CREATE TABLE public.wichert (id integer);\nALTER TABLE public.wichert OWNER TO wichert;\n",
+        ))
+        .build();
+    let mut out = Vec::new();
+    archive.map_owners(
+        Cursor::new(Vec::new()),
+        &mut out,
+        &HashMap::from([(String::from("wichert"), String::from("postgres"))]),
+    )?;
+    let archive = Archive::parse(&mut &out[..])?;
+    let defns: Vec<&str> = archive
+        .toc_entries
+        .iter()
+        .map(|e| e.defn.as_str())
+        .collect();
+    assert_eq!(
+        defns,
+        [
+            "CREATE DATABASE pizza WITH TEMPLATE = template0;\n",
+            "REVOKE CONNECT,TEMPORARY ON DATABASE pizza FROM PUBLIC;\n\
+             GRANT CONNECT ON DATABASE pizza TO postgres;\n",
+            "ALTER DEFAULT PRIVILEGES FOR ROLE postgres IN SCHEMA public GRANT SELECT ON TABLES TO postgres;\n",
+            "CREATE TABLE public.wichert (id integer);\nALTER TABLE public.wichert OWNER TO postgres;\n",
+        ]
+    );
+    assert_eq!(archive.toc_entries[0].owner, "postgres");
+    Ok(())
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "serde")]
+
+use std::fs::File;
+use std::path::Path;
+
+#[test]
+fn test_archive_round_trip() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut f = File::open(cargo_path.join("test.pgdump"))?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+
+    let json = serde_json::to_string(&archive).unwrap();
+    let decoded: pgarchive::Archive = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, archive);
+    Ok(())
+}
+
+#[test]
+fn test_toc_round_trip() -> Result<(), pgarchive::ArchiveError> {
+    let cargo_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests");
+    let mut f = File::open(cargo_path.join("test.pgdump"))?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+
+    let json = serde_json::to_string(&archive.toc_entries).unwrap();
+    let decoded: Vec<pgarchive::TocEntry> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, archive.toc_entries);
+    Ok(())
+}
+
+#[test]
+fn test_enum_representation() {
+    assert_eq!(
+        serde_json::to_string(&pgarchive::Section::PreData).unwrap(),
+        "\"pre-data\""
+    );
+    assert_eq!(
+        serde_json::to_string(&pgarchive::CompressionMethod::Gzip(6)).unwrap(),
+        "{\"Gzip\":6}"
+    );
+    assert_eq!(
+        serde_json::to_string(&pgarchive::CompressionMethod::ZSTD).unwrap(),
+        "\"ZSTD\""
+    );
+}
@@ -0,0 +1,19 @@
+use pgarchive::{Archive, SessionSettings};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::path::Path;
+
+#[test]
+fn test_session_settings() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let archive = Archive::parse(&mut File::open(path).unwrap()).unwrap();
+    assert_eq!(
+        archive.session_settings(),
+        SessionSettings {
+            client_encoding: Some(String::from("UTF8")),
+            standard_conforming_strings: Some(true),
+            search_path: Some(vec![]),
+            other: BTreeMap::new(),
+        }
+    );
+}
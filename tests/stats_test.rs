@@ -0,0 +1,26 @@
+use pgarchive::{Archive, Section};
+use std::fs::File;
+use std::path::Path;
+
+#[test]
+fn test_stats() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let archive = Archive::parse(&mut File::open(path).unwrap()).unwrap();
+    let stats = archive.stats();
+
+    assert_eq!(stats.total, 20);
+    assert_eq!(stats.desc_count("TABLE"), 3);
+    assert_eq!(stats.desc_count("TABLE DATA"), 3);
+    assert_eq!(stats.desc_count("INDEX"), 1);
+    assert_eq!(stats.desc_count("SEQUENCE"), 2);
+    assert_eq!(stats.desc_count("SEQUENCE SET"), 2);
+    assert_eq!(stats.desc_count("CONSTRAINT"), 3);
+    assert_eq!(stats.desc_count("FK CONSTRAINT"), 2);
+    assert_eq!(stats.desc_count("VIEW"), 0);
+    assert_eq!(stats.by_desc.values().sum::<usize>(), stats.total);
+
+    assert_eq!(stats.section_count(Section::PreData), 9);
+    assert_eq!(stats.section_count(Section::Data), 5);
+    assert_eq!(stats.section_count(Section::PostData), 6);
+    assert_eq!(stats.section_count(Section::None), 0);
+}
@@ -0,0 +1,94 @@
+use pgarchive::{Archive, ArchiveError, DataAvailability, Section};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+fn open() -> File {
+    File::open(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/tar.pgdump")).unwrap()
+}
+
+fn read_table(archive: &Archive, f: &mut File, tag: &str) -> Result<String, ArchiveError> {
+    let entry = archive
+        .find_toc_entry(Section::Data, "TABLE DATA", tag)
+        .expect("no table data present");
+    let mut buffer = String::new();
+    archive.read_data(f, entry)?.read_to_string(&mut buffer)?;
+    Ok(buffer)
+}
+
+#[test]
+fn test_parse_tar() -> Result<(), ArchiveError> {
+    let mut f = open();
+    let archive = Archive::parse_tar(&mut f)?;
+    assert_eq!(archive.database_name, "tarpizza");
+    assert_eq!(archive.version, "1.14.0");
+    assert!(archive
+        .find_toc_entry(Section::PreData, "TABLE", "pizza")
+        .is_some());
+
+    let data = archive
+        .find_toc_entry(Section::Data, "TABLE DATA", "pizza")
+        .unwrap();
+    // The table of contents of a tar archive does not record where the data
+    // is stored, but the archive knows the tar member.
+    assert_eq!(data.data_availability(), DataAvailability::PositionUnknown);
+    assert_eq!(archive.data_availability(data), DataAvailability::Present);
+
+    assert_eq!(
+        read_table(&archive, &mut f, "pizza")?,
+        "1\tMargherita\n2\tHawaii\n\\.\n\n\n"
+    );
+    assert_eq!(read_table(&archive, &mut f, "topping")?, "\\.\n\n\n");
+
+    // Entries without data have no tar member.
+    let table = archive
+        .find_toc_entry(Section::PreData, "TABLE", "pizza")
+        .unwrap();
+    assert_eq!(archive.data_availability(table), DataAvailability::NoData);
+    assert!(matches!(
+        archive.read_data(&mut f, table),
+        Err(ArchiveError::NotDataEntry { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_parse_tar_without_toc() {
+    let e = Archive::parse_tar(&mut &[0u8; 1024][..]).unwrap_err();
+    assert!(matches!(e, ArchiveError::InvalidData(_)));
+}
+
+#[test]
+fn test_parse_custom_as_tar() {
+    let mut f =
+        File::open(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump")).unwrap();
+    assert!(Archive::parse_tar(&mut f).is_err());
+}
+
+#[test]
+fn test_tar_table_data_size() -> Result<(), ArchiveError> {
+    let mut f = open();
+    let archive = Archive::parse_tar(&mut f)?;
+    let entry = archive
+        .find_toc_entry(Section::Data, "TABLE DATA", "pizza")
+        .unwrap();
+    assert_eq!(
+        archive.table_data_size(&mut f, entry)?,
+        read_table(&archive, &mut f, "pizza")?.len() as u64
+    );
+    Ok(())
+}
+
+#[test]
+fn test_tar_copy_table_data_raw() -> Result<(), ArchiveError> {
+    let mut f = open();
+    let archive = Archive::parse_tar(&mut f)?;
+    let entry = archive
+        .find_toc_entry(Section::Data, "TABLE DATA", "pizza")
+        .unwrap();
+    assert!(matches!(
+        archive.copy_table_data_raw(&mut f, entry, &mut Vec::new()),
+        Err(ArchiveError::InvalidData(_))
+    ));
+    Ok(())
+}
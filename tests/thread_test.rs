@@ -0,0 +1,69 @@
+#![cfg(feature = "gzip")]
+
+use pgarchive::{Archive, ArchiveError, DumpId};
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+#[test]
+fn test_concurrent_read_data() -> Result<(), ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, mut f) = Archive::open(&path)?;
+    let tables: Vec<DumpId> = archive
+        .toc_entries
+        .iter()
+        .filter(|e| e.desc == "TABLE DATA")
+        .map(|e| e.id)
+        .collect();
+    assert!(tables.len() > 1);
+
+    let mut expected = HashMap::new();
+    for &id in &tables {
+        let entry = archive.find_toc_entry_by_id(id).unwrap();
+        let mut buffer = Vec::new();
+        archive.read_data(&mut f, entry)?.read_to_end(&mut buffer)?;
+        expected.insert(id, buffer);
+    }
+
+    let archive = Arc::new(archive);
+    let handles: Vec<_> = tables
+        .iter()
+        .map(|&id| {
+            let archive = Arc::clone(&archive);
+            let path = path.clone();
+            thread::spawn(move || -> Result<(DumpId, Vec<u8>), ArchiveError> {
+                let entry = archive.find_toc_entry_by_id(id).unwrap();
+                let mut file = archive.reader_for(&path)?;
+                let mut buffer = Vec::new();
+                archive
+                    .read_data(&mut file, entry)?
+                    .read_to_end(&mut buffer)?;
+                Ok((id, buffer))
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        let (id, buffer) = handle.join().unwrap()?;
+        assert_eq!(buffer, expected[&id]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_reader_is_send() -> Result<(), ArchiveError> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let (archive, f) = Archive::open(&path)?;
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    // Passing the file by value gives a reader which does not borrow it.
+    let mut data = archive.read_data(f, entry)?;
+    let size = thread::spawn(move || std::io::copy(&mut data, &mut std::io::sink()))
+        .join()
+        .unwrap()?;
+    assert!(size > 0);
+    Ok(())
+}
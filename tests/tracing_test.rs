@@ -0,0 +1,44 @@
+#![cfg(feature = "tracing")]
+
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Writer which collects all log output in a shared buffer.
+#[derive(Clone, Default)]
+struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Buffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_parse_events() -> Result<(), pgarchive::ArchiveError> {
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/test.pgdump");
+    let archive =
+        tracing::subscriber::with_default(subscriber, || pgarchive::Archive::open(path))?.0;
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    let events: Vec<&str> = output
+        .lines()
+        .filter(|l| l.contains("read TOC entry"))
+        .collect();
+    assert_eq!(events.len(), archive.toc_entries.len());
+    assert!(events.iter().all(|l| l.contains("parse:")));
+    assert!(events[0].contains(&format!("id={}", archive.toc_entries[0].id)));
+    Ok(())
+}
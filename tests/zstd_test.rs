@@ -0,0 +1,65 @@
+#![cfg(feature = "zstd")]
+mod common;
+
+use common::{build_archive, write_archive, Entry};
+use std::io::{Read, Write};
+
+#[test]
+fn test_zstd_long_table_data() -> Result<(), pgarchive::ArchiveError> {
+    let expected: Vec<u8> = (0..20_000)
+        .flat_map(|i| format!("{}\tpizza number {}\n", i, i % 97).into_bytes())
+        .collect();
+
+    let mut encoder = zstd::Encoder::new(Vec::new(), 19)?;
+    encoder.long_distance_matching(true)?;
+    encoder.window_log(30)?;
+    encoder.write_all(&expected)?;
+    let compressed = encoder.finish()?;
+    let chunks = compressed.chunks(4096).map(|c| c.to_vec()).collect();
+
+    let file = write_archive(&build_archive(3, &[Entry::table_data(1, "pizza", chunks)]));
+    let mut f = file.reopen()?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    assert_eq!(
+        archive.compression_method,
+        pgarchive::CompressionMethod::ZSTD
+    );
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    let mut buffer = Vec::new();
+    archive.read_data(&mut f, entry)?.read_to_end(&mut buffer)?;
+    assert_eq!(buffer, expected);
+    Ok(())
+}
+
+#[test]
+fn test_zstd_dictionary() -> Result<(), pgarchive::ArchiveError> {
+    let frame = vec![
+        0x28, 0xb5, 0x2f, 0xfd, // magic number
+        0x01, // frame header descriptor: 1 byte dictionary id
+        0x00, // window descriptor
+        0x2a, // dictionary id
+        0x00, 0x00, 0x00, 0x00,
+    ];
+    let file = write_archive(&build_archive(
+        3,
+        &[Entry::table_data(1, "pizza", vec![frame])],
+    ));
+    let mut f = file.reopen()?;
+    let archive = pgarchive::Archive::parse(&mut f)?;
+    let entry = archive
+        .find_toc_entry(pgarchive::Section::Data, "TABLE DATA", "pizza")
+        .expect("no data for pizza table present");
+    match archive.read_data(&mut f, entry) {
+        Err(e @ pgarchive::ArchiveError::DictionaryNotSupported(42)) => {
+            assert_eq!(
+                e.to_string(),
+                "dictionary-compressed data not supported (dictionary id 42)"
+            );
+        }
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("reading dictionary-compressed data did not fail"),
+    }
+    Ok(())
+}